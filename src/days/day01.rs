@@ -1,4 +1,4 @@
-use super::Day;
+use super::{Day, Param, Params};
 
 pub struct Day01;
 
@@ -30,23 +30,42 @@ impl Day01 {
 
         Ok(rotations)
     }
+
+    fn dial(params: &Params) -> Result<(i64, i64), String> {
+        let start = params.get::<i64>("start")?;
+        let total_ticks = params.get::<i64>("ticks")?;
+        if total_ticks <= 0 {
+            return Err(format!("The dial needs at least one tick, got {}", total_ticks));
+        }
+        Ok((start.rem_euclid(total_ticks), total_ticks))
+    }
 }
 
 impl Day for Day01 {
-    fn part1(&self, input: &str) -> String {
+    fn params(&self) -> Vec<Param> {
+        vec![
+            Param::new("start", "50", "position the dial starts at"),
+            Param::new("ticks", "100", "number of ticks on the dial"),
+        ]
+    }
+
+    fn part1(&self, input: &str, params: &Params) -> String {
         let rotations = match Self::get_ticks(input) {
             Ok(rotations) => rotations,
             Err(err) => return err,
         };
+        let (start, total_ticks) = match Self::dial(params) {
+            Ok(dial) => dial,
+            Err(err) => return err,
+        };
 
-        let mut pos: i64 = 50;
-        const TOTAL_TICKS: i64 = 100;
+        let mut pos: i64 = start;
         let mut count = 0;
 
         rotations.iter().for_each(
             |rota| {
-                // rem_euclid results in the remainder in space of 0 <= rem < TOTAL_TICKS
-                pos = (pos + rota).rem_euclid(TOTAL_TICKS);
+                // rem_euclid results in the remainder in space of 0 <= rem < total_ticks
+                pos = (pos + rota).rem_euclid(total_ticks);
                 if pos == 0 {
                     count += 1
                 }
@@ -56,14 +75,17 @@ impl Day for Day01 {
         count.to_string()
     }
 
-    fn part2(&self, input: &str) -> String {
+    fn part2(&self, input: &str, params: &Params) -> String {
         let rotations = match Self::get_ticks(input) {
             Ok(rotations) => rotations,
             Err(err) => return err,
         };
+        let (start, total_ticks) = match Self::dial(params) {
+            Ok(dial) => dial,
+            Err(err) => return err,
+        };
 
-        let mut pos: i64 = 50;
-        const TOTAL_TICKS: i64 = 100;
+        let mut pos: i64 = start;
         let mut count = 0;
         let mut last_count = 0;
 
@@ -72,7 +94,7 @@ impl Day for Day01 {
                 last_count = count;
                 let new_pos = pos + rota;
                 
-                count += (new_pos / TOTAL_TICKS).abs();
+                count += (new_pos / total_ticks).abs();
 
                 if new_pos < 0 && pos != 0 {
                     count += 1;
@@ -81,7 +103,7 @@ impl Day for Day01 {
                     count += 1;
                 }
                 
-                pos = new_pos.rem_euclid(TOTAL_TICKS);
+                pos = new_pos.rem_euclid(total_ticks);
             }
         );
 
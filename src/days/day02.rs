@@ -1,4 +1,4 @@
-use super::Day;
+use super::{Day, Params};
 
 use std::collections::HashMap;
 
@@ -23,7 +23,7 @@ impl Day02 {
 }
 
 impl Day for Day02 {
-    fn part1(&self, input: &str) -> String {
+    fn part1(&self, input: &str, _params: &Params) -> String {
         let ranges = Self::parse(input);
         let mut sum: i64 = 0;
         let mut memo: HashMap<(i64, i64), i64> = HashMap::new();
@@ -48,7 +48,7 @@ impl Day for Day02 {
         sum.to_string()
     }
 
-    fn part2(&self, input: &str) -> String {
+    fn part2(&self, input: &str, _params: &Params) -> String {
         let ranges = Self::parse(input);
         let mut sum: i64 = 0;
 
@@ -1,4 +1,4 @@
-use super::Day;
+use super::{Day, Param, Params};
 
 pub struct Day03;
 
@@ -15,7 +15,11 @@ impl Day03 {
 }
 
 impl Day for Day03 {
-    fn part1(&self, input: &str) -> String {
+    fn params(&self) -> Vec<Param> {
+        vec![Param::new("batteries", "12", "batteries to turn on per bank in part 2")]
+    }
+
+    fn part1(&self, input: &str, _params: &Params) -> String {
         let banks = Self::parse(input);
         let mut sum: i128 = 0;
 
@@ -47,27 +51,32 @@ impl Day for Day03 {
         sum.to_string()
     }
 
-    fn part2(&self, input: &str) -> String {
-        const ARR_SIZE: usize = 12;
+    fn part2(&self, input: &str, params: &Params) -> String {
+        let arr_size = match params.get::<usize>("batteries") {
+            Ok(n) if (1..=19).contains(&n) => n,
+            Ok(n) => return format!("Can only turn on 1 to 19 batteries per bank, got {}", n),
+            Err(err) => return err,
+        };
         let banks = Self::parse(input);
-        let mut sum: u64 = 0;
+        // 19 digits fit a u64 but a few banks of them summed up don't
+        let mut sum: u128 = 0;
         
         // This solution is a further development from part 1 and can be applied to part 1 too
         // It just doesnt stop the iteration over the bank early instead looking at all bank entries and then checking
         // valid left final numer slots if it should go there
         // If it should all digits to the right of it have to be reset to 0 as the battery can't be left of it.
-        // I kept part 1 and 2 seperate and duplicate to show the evolution of my solution, but this same method should be useable for no 2, just by replacing the 12 const with 2
+        // I kept part 1 and 2 seperate and duplicate to show the evolution of my solution, but this same method should be useable for no 2, just by setting the batteries param to 2
         for bank in &banks {
-            let mut max_vals: [u8; ARR_SIZE] = [0; ARR_SIZE];
+            let mut max_vals = vec![0u8; arr_size];
             
             // Optimization potential: instead of iterating over each battery in the bank, is it faster to
             // fill each digit by iterating over the left subset of the bank? feels like should be similar but yes
             let mut empty_from = 0;
             for (idx, i) in bank.iter().enumerate() {
                 let left_to_end = bank.len() - idx;
-                let start = if left_to_end >= ARR_SIZE { 0 } else { ARR_SIZE - left_to_end };
+                let start = if left_to_end >= arr_size { 0 } else { arr_size - left_to_end };
                 
-                for y in start..ARR_SIZE {
+                for y in start..arr_size {
                     if *i > max_vals[y] || empty_from <= y {
                         max_vals[y] = *i;
                         empty_from = y + 1;
@@ -76,9 +85,9 @@ impl Day for Day03 {
                 }
             }
 
-            for i in 0..ARR_SIZE {
-                let pow = 10u64.pow((ARR_SIZE - 1 - i) as u32);
-                sum += max_vals[i] as u64 * pow;
+            for i in 0..arr_size {
+                let pow = 10u128.pow((arr_size - 1 - i) as u32);
+                sum += max_vals[i] as u128 * pow;
             }
         }
 
@@ -1,4 +1,6 @@
-use super::Day;
+use super::{Day, Param, Params};
+use crate::utils::automaton::{Automaton, Boundary, Rule, UpdateMode};
+use crate::utils::render::{FrameSink, Rgb};
 
 pub struct Day04;
 
@@ -99,37 +101,41 @@ impl Day04 {
         count
     }
 
-    fn print_grid(grid: &Vec<Vec<u8>>) {
-        for row in grid {
-            println!("{}", row.iter().map(|&cell| cell.to_string()).collect::<Vec<String>>().join(" "));
-        }
+    // The removal process as an automaton. Without a rule a roll simply stays while it has at
+    // least 4 neighbours and nothing ever appears again.
+    fn automaton(grid: &[Vec<u8>], params: &Params) -> Result<Automaton, String> {
+        let rule = match params.str("rule") {
+            "" => Rule::custom(|alive, neighbours| alive && neighbours >= 4),
+            notation => Rule::parse(notation)?,
+        };
+
+        Ok(Automaton::new(grid, rule)
+            .with_boundary(params.get::<Boundary>("boundary")?)
+            .with_update(params.get::<UpdateMode>("update")?))
     }
 
-    fn update_neighbors(grid: &mut Vec<Vec<u8>>, result: &mut Vec<Vec<u8>>, i: usize, j: usize, kernel: &Vec<Vec<u8>>) {
-        let kernel_size = kernel.len();
-        let pad = kernel_size / 2;
-
-        // To adapt to different kernal sizes we compute all the neighbour indexes 
-        // that took this cell into account in their convolution
-        for ki in 0..kernel_size {
-            for kj in 0..kernel_size {
-                // Calc actual neighbour index
-                let ni = i as isize + ki as isize - pad as isize;
-                let nj = j as isize + kj as isize - pad as isize;
-
-                // if Neighbour real cell (in grid boundaries) then subtract one.
-                if ni >= 0 && nj >= 0 && (ni as usize) < grid.len() && (nj as usize) < grid[0].len() {
-                    if result[ni as usize][nj as usize] > 0 {
-                        result[ni as usize][nj as usize] -= 1;
-                    }
-                }
-            }
-        }
+    // Rolls that are still there, rolls removed in the last round and empty floor
+    fn colour_frame(prev: &[Vec<u8>], grid: &[Vec<u8>]) -> Vec<Vec<Rgb>> {
+        prev.iter().zip(grid).map(|(prev_row, row)| {
+            prev_row.iter().zip(row).map(|(&before, &now)| match (before, now) {
+                (_, 1) => (110, 190, 90),
+                (1, 0) => (220, 60, 50),
+                _ => (25, 25, 35),
+            }).collect()
+        }).collect()
     }
 }
 
 impl Day for Day04 {
-    fn part1(&self, input: &str) -> String {
+    fn params(&self) -> Vec<Param> {
+        vec![
+            Param::new("rule", "", "B/S rule to run in part 2 instead of the roll removal, e.g. B3/S23"),
+            Param::new("boundary", "bounded", "bounded or toroidal grid in part 2"),
+            Param::new("update", "sync", "sync or async (in place, row by row) update in part 2"),
+        ]
+    }
+
+    fn part1(&self, input: &str, _params: &Params) -> String {
         let grid = Self::parse(input);
 
         let kernel = vec![
@@ -150,44 +156,46 @@ impl Day for Day04 {
         count.to_string()
     }
 
-    fn part2(&self, input: &str) -> String {
-        let mut grid = Self::parse(input);
-        let mut total_removed = 0;
+    fn part2(&self, input: &str, params: &Params) -> String {
+        let grid = Self::parse(input);
 
-        let kernel = vec![
-            vec![1, 1, 1],
-            vec![1, 0, 1],
-            vec![1, 1, 1],
-        ];
+        // The whole removal process is just a life-like rule run until it settles
+        let mut automaton = match Self::automaton(&grid, params) {
+            Ok(automaton) => automaton,
+            Err(err) => return err,
+        };
+        let initial = automaton.population();
 
-        let mut result = Self::direct_conv(&grid, &kernel);
-        Self::mask_result(&grid, &mut result);
+        automaton.run(usize::MAX);
 
-        loop {
-            let mut count = 0;
-            let mut to_remove = Vec::new();
-
-            for i in 0..grid.len() {
-                for j in 0..grid[0].len() {
-                    if grid[i][j] != 0 && result[i][j] < 4 {
-                        to_remove.push((i, j));
-                        total_removed += 1;
-                        count += 1;
-                    }
-                }
-            }
+        // Rules with births can end up with more cells than they started with
+        (initial as i64 - automaton.population() as i64).to_string()
+    }
 
-            for &(i, j) in &to_remove {
-                grid[i][j] = 0;
-                Self::update_neighbors(&mut grid, &mut result, i, j, &kernel);
-            }
+    // One frame per removal round, with the rolls removed in that round highlighted
+    fn visualize(&self, input: &str, params: &Params, target: &str) -> Result<(), String> {
+        let grid = Self::parse(input);
+        let mut sink = FrameSink::parse(target)?;
 
-            if count == 0 {
-                break;
-            }
-        }
+        let mut automaton = Self::automaton(&grid, params)?;
+        let initial = automaton.population();
+
+        let mut prev = automaton.grid();
+        sink.write(&Self::colour_frame(&prev, &prev), &format!("Round 0: {} rolls", initial))?;
 
-        println!("Final grid:");
-        total_removed.to_string()
+        let mut result = Ok(());
+        automaton.run_with(usize::MAX, |automaton, removed| {
+            if removed == 0 || result.is_err() {
+                return;
+            }
+            let grid = automaton.grid();
+            let caption = format!("Round {}: removed {}, {} rolls left", automaton.generation(), removed, automaton.population());
+            result = sink.write(&Self::colour_frame(&prev, &grid), &caption);
+            prev = grid;
+        });
+        result?;
+
+        println!("Removed {} rolls in total", initial as i64 - automaton.population() as i64);
+        Ok(())
     }
 }
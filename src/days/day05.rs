@@ -1,4 +1,4 @@
-use super::Day;
+use super::{Day, Params};
 
 pub struct Day05;
 
@@ -39,7 +39,7 @@ impl Day05 {
 }
 
 impl Day for Day05 {
-    fn part1(&self, input: &str) -> String {
+    fn part1(&self, input: &str, _params: &Params) -> String {
         let (final_ranges, ids) = Self::parse(input);
 
         ids.iter().fold(0, |acc, id| {
@@ -51,7 +51,7 @@ impl Day for Day05 {
         }).to_string()
     }
 
-    fn part2(&self, input: &str) -> String {
+    fn part2(&self, input: &str, _params: &Params) -> String {
         let (final_ranges, _) = Self::parse(input);
 
         let mut final_fresh = 0i64;
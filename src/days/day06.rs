@@ -1,4 +1,4 @@
-use super::Day;
+use super::{Day, Params};
 
 pub struct Day06;
 
@@ -111,13 +111,13 @@ impl Day06 {
 }
 
 impl Day for Day06 {
-    fn part1(&self, input: &str) -> String {
+    fn part1(&self, input: &str, _params: &Params) -> String {
         let rows_to_evaluate = Self::parse(input);
         
         Self::compute_row_sum(rows_to_evaluate).to_string()
     }
 
-    fn part2(&self, input: &str) -> String {
+    fn part2(&self, input: &str, _params: &Params) -> String {
         let rows_to_evaluate = Self::parse_2(input);
         
         Self::compute_row_sum(rows_to_evaluate).to_string()
@@ -1,4 +1,4 @@
-use super::Day;
+use super::{Day, Params};
 use std::sync::{Arc, Mutex};
 use std::collections::{HashSet, HashMap};
 use std::thread;
@@ -84,7 +84,7 @@ impl Day07 {
 }
 
 impl Day for Day07 {
-    fn part1(&self, input: &str) -> String {
+    fn part1(&self, input: &str, _params: &Params) -> String {
         println!("Start!");
         let char_matrix: Vec<Vec<char>> = Self::parse(input);
         
@@ -102,7 +102,7 @@ impl Day for Day07 {
         m_count.lock().unwrap().to_string()
     }
 
-    fn part2(&self, input: &str) -> String {
+    fn part2(&self, input: &str, _params: &Params) -> String {
         let char_matrix: Vec<Vec<char>> = Self::parse(input);
 
         let mut beams = HashMap::new();
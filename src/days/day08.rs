@@ -1,152 +1,156 @@
-use super::Day;
-use std::{cmp::Reverse, collections::BinaryHeap};
+use super::{Day, Param, Params};
+use crate::utils::clustering::SingleLinkage;
+use crate::utils::geometry::{Metric, Point};
+use crate::utils::kd_tree::KdTree;
+
+// Points are const generic, so the dimension read from the input picks the instance to call.
+// Wider points than that take the runtime dimension path instead.
+macro_rules! by_dimension {
+    ($dims:expr, $this:ident . $method:ident ( $($arg:expr),* ), $wide:expr) => {
+        match $dims {
+            1 => $this.$method::<1>($($arg),*),
+            2 => $this.$method::<2>($($arg),*),
+            3 => $this.$method::<3>($($arg),*),
+            4 => $this.$method::<4>($($arg),*),
+            5 => $this.$method::<5>($($arg),*),
+            6 => $this.$method::<6>($($arg),*),
+            _ => $wide,
+        }
+    };
+}
 
 pub struct Day08;
 
-pub struct Point {
-    x: i64,
-    y: i64,
-    z: i64,
-}
+impl Day08 {
+    // Number of coordinates per junction box, taken from the first line
+    fn dimensions(input: &str) -> usize {
+        input.lines().find(|line| !line.trim().is_empty()).map_or(0, |line| line.split(',').count())
+    }
 
-impl Point {
-    pub fn new(x: i64, y: i64, z: i64) -> Self {
-        Self { x, y, z }
+    fn parse<const D: usize>(input: &str) -> Result<Vec<Point<D>>, String> {
+        Point::<D>::parse_lines(input)
     }
-}
 
-struct UnionFind {
-    parent: Vec<usize>,
-    rank: Vec<usize>,
-}
+    // Points with any number of coordinates, all lines need as many as the first one
+    fn parse_wide(input: &str) -> Result<Vec<Vec<i64>>, String> {
+        let dims = Self::dimensions(input);
+        input.lines().enumerate()
+            .filter(|(_, line)| !line.trim().is_empty())
+            .map(|(idx, line)| {
+                let coords = line.trim().split(',')
+                    .map(|c| c.trim().parse::<i64>().map_err(|_| format!("Line {}: invalid coordinate '{}'", idx + 1, c.trim())))
+                    .collect::<Result<Vec<i64>, String>>()?;
+                if coords.len() != dims {
+                    return Err(format!("Line {}: expected {} comma separated coordinates but got {}", idx + 1, dims, coords.len()));
+                }
+                Ok(coords)
+            })
+            .collect()
+    }
 
-impl UnionFind {
-    pub fn new(size: usize) -> Self {
-        Self {
-            parent: (0..size).collect(),
-            rank: vec![0; size],
-        }
+    // The metric on runtime sized points
+    fn wide_distance(metric: &Metric, a: &[i64], b: &[i64]) -> i64 {
+        metric.combine(a.iter().zip(b).map(|(x, y)| (x - y).abs()))
     }
 
-    pub fn find(&mut self, x: usize) -> usize {
-        if self.parent[x] != x {
-            self.parent[x] = self.find(self.parent[x]);
-        }
-        self.parent[x]
-    }
-
-    pub fn union(&mut self, x: usize, y: usize) {
-        let root_x = self.find(x);
-        let root_y = self.find(y);
-
-        if root_x != root_y {
-            if self.rank[root_x] > self.rank[root_y] {
-                self.parent[root_y] = root_x;
-            } else if self.rank[root_x] < self.rank[root_y] {
-                self.parent[root_x] = root_y;
-            } else {
-                self.parent[root_y] = root_x;
-                self.rank[root_x] += 1;
-            }
-        }
+    // Connect the closest pairs and multiply the sizes of the largest circuits
+    fn circuits(clustering: &SingleLinkage, params: &Params) -> Result<String, String> {
+        let connections = params.get::<usize>("connections")?;
+        let largest = params.get::<usize>("largest")?;
+        let largest_circuits = clustering.largest_clusters(connections, largest);
+
+        Ok(largest_circuits.iter().product::<usize>().to_string())
     }
-    
-    pub fn are_all_same_root(&mut self) -> bool {
-        let first_root = self.find(0);
-        for i in 1..self.parent.len() {
-            if self.find(i) != first_root {
-                return false;
-            }
+
+    // The last edge of the minimum spanning tree is the one that connects everything
+    fn last_connection<P>(points: &[P], clustering: &SingleLinkage, x: impl Fn(&P) -> i64) -> String {
+        let result = match clustering.connecting_edge() {
+            Some(edge) => x(&points[edge.a]) * x(&points[edge.b]),
+            None => 0,
+        };
+
+        result.to_string()
+    }
+
+    // One CSV row per merge: the metric distance of the new connection (squared for euclidean, like
+    // everywhere else), what the circuits look like afterwards and the dendrogram step
+    // (clusters 0..n are the boxes, merge i creates cluster n + i)
+    fn percolation_csv(clustering: &SingleLinkage) -> String {
+        let mut csv = String::from("merge,a,b,distance,components,largest_component,left,right,size\n");
+        for (merge, (step, link)) in clustering.percolation().iter().zip(clustering.dendrogram()).enumerate() {
+            csv.push_str(&format!(
+                "{},{},{},{},{},{},{},{},{}\n",
+                merge + 1, step.edge.a, step.edge.b, step.edge.dist, step.components, step.largest,
+                link.left, link.right, link.size
+            ));
         }
-        true
+        csv
     }
-}
 
-impl Day08 {
-    fn parse(input: &str) -> Vec<Point> {
-        input.lines().map(|row| {
-            let split = row.trim().split(",").map(|x| x.parse::<i64>().unwrap()).collect::<Vec<i64>>();
-            Point::new(split[0], split[1], split[2])
-        }).collect::<Vec<Point>>()
+    fn solve_part1<const D: usize>(&self, input: &str, params: &Params) -> Result<String, String> {
+        let points = Self::parse::<D>(input)?;
+        let index = KdTree::with_metric(points, params.get::<Metric>("metric")?);
+        Self::circuits(&SingleLinkage::indexed(&index), params)
     }
 
-    fn squared_distance(a: &Point, b: &Point) -> i64 {
-        let dx = a.x - b.x;
-        let dy = a.y - b.y;
-        let dz = a.z - b.z;
-        dx * dx + dy * dy + dz * dz
+    // No k-d tree for runtime sized points, the closest pairs come from looking at all of them
+    fn solve_part1_wide(&self, input: &str, params: &Params) -> Result<String, String> {
+        let points = Self::parse_wide(input)?;
+        let metric = params.get::<Metric>("metric")?;
+        Self::circuits(&SingleLinkage::new(&points, |a, b| Self::wide_distance(&metric, a, b)), params)
     }
-}
 
-impl Day for Day08 {
-    fn part1(&self, input: &str) -> String {
-        let points = Self::parse(input);
-        let mut heap = BinaryHeap::new();
-        let mut uf = UnionFind::new(points.len());
-
-        // Populate the heap with all pairwise distances
-        for (i, point) in points.iter().enumerate() {
-            for j in (i + 1)..points.len() {
-                let other_point = &points[j];
-                let dist = Self::squared_distance(point, other_point);
-                heap.push(Reverse((dist, i, j)));
-            }
-        }
+    fn solve_part2<const D: usize>(&self, input: &str, params: &Params) -> Result<String, String> {
+        let points = Self::parse::<D>(input)?;
+        let metric = params.get::<Metric>("metric")?;
+        let clustering = SingleLinkage::new(&points, |a, b| metric.distance(a, b));
+        Ok(Self::last_connection(&points, &clustering, |p| p.x()))
+    }
 
-        let mut iterations = 0;
-        while iterations < 1000 {
-            if let Some(Reverse((_dist, i, j))) = heap.pop() {
-                uf.union(i, j);
-                iterations += 1;
-            } else {
-                break; // Exit if the heap is empty
-            }
-        }
+    fn solve_part2_wide(&self, input: &str, params: &Params) -> Result<String, String> {
+        let points = Self::parse_wide(input)?;
+        let metric = params.get::<Metric>("metric")?;
+        let clustering = SingleLinkage::new(&points, |a, b| Self::wide_distance(&metric, a, b));
+        Ok(Self::last_connection(&points, &clustering, |p| p[0]))
+    }
 
-        // Count the size of each connected component
-        let mut component_sizes = vec![0; points.len()];
-        for i in 0..points.len() {
-            let root = uf.find(i);
-            component_sizes[root] += 1;
-        }
+    fn export_csv<const D: usize>(&self, input: &str, params: &Params) -> Result<String, String> {
+        let points = Self::parse::<D>(input)?;
+        let metric = params.get::<Metric>("metric")?;
+        Ok(Self::percolation_csv(&SingleLinkage::new(&points, |a, b| metric.distance(a, b))))
+    }
 
-        // Find the sizes of the three largest components
-        component_sizes.sort_unstable_by(|a, b| b.cmp(a));
-        let largest_three: Vec<_> = component_sizes.into_iter().take(3).collect();
+    fn export_csv_wide(&self, input: &str, params: &Params) -> Result<String, String> {
+        let points = Self::parse_wide(input)?;
+        let metric = params.get::<Metric>("metric")?;
+        Ok(Self::percolation_csv(&SingleLinkage::new(&points, |a, b| Self::wide_distance(&metric, a, b))))
+    }
+}
 
-        largest_three.iter().product::<usize>().to_string()
+impl Day for Day08 {
+    fn params(&self) -> Vec<Param> {
+        vec![
+            Param::new("connections", "1000", "closest pairs to connect in part 1"),
+            Param::new("largest", "3", "number of largest circuits to multiply in part 1"),
+            Param::new("metric", "euclidean", "euclidean (squared distances), manhattan or chebyshev, optionally with axis weights like manhattan:1,1,2"),
+        ]
     }
 
-    fn part2(&self, input: &str) -> String {
-        let points = Self::parse(input);
-        let mut heap = BinaryHeap::new();
-        let mut uf = UnionFind::new(points.len());
+    fn part1(&self, input: &str, params: &Params) -> String {
+        by_dimension!(Self::dimensions(input), self.solve_part1(input, params), self.solve_part1_wide(input, params))
+            .unwrap_or_else(|err| err)
+    }
 
-        // Populate the heap with all pairwise distances
-        for (i, point) in points.iter().enumerate() {
-            for j in (i + 1)..points.len() {
-                let other_point = &points[j];
-                let dist = Self::squared_distance(point, other_point);
-                heap.push(Reverse((dist, i, j)));
-            }
-        }
+    fn part2(&self, input: &str, params: &Params) -> String {
+        by_dimension!(Self::dimensions(input), self.solve_part2(input, params), self.solve_part2_wide(input, params))
+            .unwrap_or_else(|err| err)
+    }
 
-        let mut iterations = 0;
-        let mut result = 0;
-        while true {
-            if let Some(Reverse((_dist, i, j))) = heap.pop() {
-                uf.union(i, j);
-                if uf.are_all_same_root() {
-                    println!("All connected after {} iterations with idx {} and {}", iterations, i, j);
-                    result = points[i].x * points[j].x;
-                    break;
-                }
-                iterations += 1;
-            } else {
-                break; // Exit if the heap is empty
-            }
-        }
+    fn export(&self, input: &str, params: &Params, path: &str) -> Result<(), String> {
+        let csv = by_dimension!(Self::dimensions(input), self.export_csv(input, params), self.export_csv_wide(input, params))?;
 
-        result.to_string()
+        std::fs::write(path, csv).map_err(|e| format!("Could not write {}: {}", path, e))?;
+        println!("Wrote percolation curve to {}", path);
+        Ok(())
     }
 }
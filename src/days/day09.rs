@@ -1,138 +1,342 @@
-use super::Day;
+use super::{Day, Param, Params};
 use std::collections::BinaryHeap;
 use std::cmp::Reverse;
-use geo::{Contains, Coord, Polygon, Rect};
+use crate::utils::corner_rectangles::{self, CornerRect};
+use crate::utils::geometry::Point2;
+use crate::utils::polygon::{self, Defect, OrthoPolygon, OrthoRegion, Validation, Winding};
+use crate::utils::raster::{Band, CellGrid};
+use crate::utils::rectilinear::RectilinearPolygon;
+use crate::utils::render::{FrameSink, Rgb};
 
 pub struct Day09;
 
-#[derive(Debug, Copy, Clone)]
-pub struct Point {
-    x: i64, 
-    y: i64,
+// One loop of the input with the line number of every vertex
+#[derive(Default)]
+struct Outline {
+    points: Vec<Point2>,
+    lines: Vec<usize>,
 }
 
 impl Day09 {
-    fn parse(input: &str) -> Vec<Point> {
-        input.lines().map(|row| {
-            let split = row.trim().split(",").map(|x| x.parse::<i64>().unwrap()).collect::<Vec<i64>>();
-            Point { x: split[0], y: split[1] }
-        }).collect::<Vec<Point>>()
+    fn parse(input: &str) -> Result<Vec<Point2>, String> {
+        Point2::parse_lines(input)
     }
 
-    // Wasn't used in the end
-    fn get_bin_matrix(points: &Vec<Point>) -> Vec<Vec<u8>> {
-        let mut max_x = 0;
-        let mut max_y = 0;
-        
-        for point in points {
-            if point.x > max_x {
-                max_x = point.x;
-            }
-            if point.y > max_y {
-                max_y = point.y;
+    // Loops are separated by blank lines, every loop keeps the input line of each vertex
+    fn parse_loops(input: &str) -> Result<Vec<Outline>, String> {
+        let mut loops = vec![Outline::default()];
+        for (idx, line) in input.lines().enumerate() {
+            if line.trim().is_empty() {
+                if !loops.last().unwrap().points.is_empty() {
+                    loops.push(Outline::default());
+                }
+                continue;
             }
+            let point = line.parse::<Point2>().map_err(|err| format!("Line {}: {}", idx + 1, err))?;
+            let outline = loops.last_mut().unwrap();
+            outline.points.push(point);
+            outline.lines.push(idx + 1);
         }
+        loops.retain(|l| !l.points.is_empty());
+        Ok(loops)
+    }
+
+    // The red tiles in order around every loop, each step has to stay in a row or column
+    fn parse_region(input: &str) -> Result<OrthoRegion, String> {
+        let loops = Self::parse_loops(input)?.into_iter()
+            .map(|l| OrthoPolygon::new(l.points).map_err(|err| format!("Loop starting on line {}: {}", l.lines[0], err)))
+            .collect::<Result<Vec<OrthoPolygon>, String>>()?;
+        Ok(OrthoRegion::new(loops))
+    }
 
-        let mut matrix = vec![vec![0u8; (max_x + 1) as usize]; (max_y + 1) as usize];
+    // Problems of a single loop, described by input lines
+    fn describe_defects(outline: &Outline, validation: &Validation) -> Vec<(bool, String)> {
+        let (points, lines) = (&outline.points, &outline.lines);
+        let edge = |i: usize| format!("{}-{}", lines[i], lines[(i + 1) % lines.len()]);
 
-        // By utilizing the knowledge that passing an edge means we are inside the shape, we can fill the shape
-        let mut vert_lines = vec![];
+        validation.defects.iter().map(|defect| {
+            let text = match *defect {
+                Defect::NotAxisAligned { edge: i } => {
+                    format!("Lines {}: edge from {} to {} is not axis aligned", edge(i), points[i], points[(i + 1) % points.len()])
+                }
+                Defect::DuplicateVertex { first, again } => {
+                    format!("Lines {} and {}: duplicate vertex {}", lines[first], lines[again], points[first])
+                }
+                Defect::Collinear { vertex } => {
+                    format!("Line {}: vertex {} lies in the middle of a straight run", lines[vertex], points[vertex])
+                }
+                Defect::SelfIntersection { edge_a, edge_b } => {
+                    format!("Lines {} and {}: edges intersect", edge(edge_a), edge(edge_b))
+                }
+            };
+            (defect.is_error(), text)
+        }).collect()
+    }
+
+    // The `keep` largest rectangles between two red tiles that pass `fits`, largest first
+    fn ranked_rectangles(points: &[Point2], keep: usize, fits: impl Fn(usize, usize) -> bool) -> Vec<CornerRect> {
+        let mut heap = BinaryHeap::with_capacity(keep + 1);
         for i in 0..points.len() {
-            let p1 = &points[i];
             for j in i + 1..points.len() {
-                let p2 = &points[j];
-                if p1.x == p2.x {
-                    vert_lines.push((p1.x, p1.y.min(p2.y), p1.y.max(p2.y)));
+                let area = corner_rectangles::tile_area(&points[i], &points[j]);
+                let worth_checking = heap.len() < keep || heap.peek().is_some_and(|&Reverse((smallest, _, _))| area > smallest);
+                if worth_checking && fits(i, j) {
+                    heap.push(Reverse((area, i, j)));
+                    if heap.len() > keep {
+                        heap.pop();
+                    }
+                }
+            }
+        }
+        heap.into_sorted_vec().into_iter().map(|Reverse((_, i, j))| CornerRect::new(points[i], points[j])).collect()
+    }
+
+    // Samples the rasterized tiles at real proportions, the longer side gets `size` pixels
+    fn raster_frame(grid: &CellGrid, vertices: &[Point2], best: Option<&CornerRect>, size: usize) -> Vec<Vec<Rgb>> {
+        let (first_x, first_y) = (grid.columns()[0].start, grid.rows()[0].start);
+        let last = |bands: &[Band]| bands.last().map_or(0, |b| b.start + b.len);
+        let (span_x, span_y) = (last(grid.columns()) - first_x, last(grid.rows()) - first_y);
+
+        let step = (span_x.max(span_y) as f64 / size as f64).max(1.0);
+        let (w, h) = ((span_x as f64 / step).ceil() as usize, (span_y as f64 / step).ceil() as usize);
+        let to_real = |pixel: usize, first: i64| first + (pixel as f64 * step) as i64;
+
+        let mut frame = (0..h).map(|j| {
+            let y = to_real(j, first_y);
+            (0..w).map(|i| {
+                let x = to_real(i, first_x);
+                let in_best = best.is_some_and(|r| {
+                    r.a.x().min(r.b.x()) <= x && x <= r.a.x().max(r.b.x()) && r.a.y().min(r.b.y()) <= y && y <= r.a.y().max(r.b.y())
+                });
+                match (grid.contains_tile(x, y), in_best) {
+                    (true, true) => (66, 133, 244),
+                    (true, false) => (46, 125, 50),
+                    _ => (24, 24, 24),
                 }
+            }).collect::<Vec<Rgb>>()
+        }).collect::<Vec<Vec<Rgb>>>();
+
+        for p in vertices {
+            let (i, j) = (((p.x() - first_x) as f64 / step) as usize, ((p.y() - first_y) as f64 / step) as usize);
+            if j < h && i < w {
+                frame[j][i] = (211, 47, 47);
             }
         }
+        frame
+    }
 
-        // Implement point in polygon with scanline algorithm
-        let mut active = BinaryHeap::<Reverse<(i64, i64)>>::new();
+    // Polygon with the best rectangles of both parts on top, the runners-up dashed behind them
+    fn svg(region: &OrthoRegion, part1: &[CornerRect], part2: &[CornerRect]) -> String {
+        const SIZE: f64 = 1000.0;
+        const MARGIN: f64 = 20.0;
 
-        for y in 0..=max_y {
-            // keep edges where y < end_y
-            active.retain(|&Reverse((_, end_y))| y < end_y + 1);
+        let (mut min_x, mut min_y, mut max_x, mut max_y) = (i64::MAX, i64::MAX, i64::MIN, i64::MIN);
+        for p in region.vertices() {
+            (min_x, min_y, max_x, max_y) = (min_x.min(p.x()), min_y.min(p.y()), max_x.max(p.x()), max_y.max(p.y()));
+        }
 
-            // add edges that start at this y
-            for &(x, start_y, end_y) in &vert_lines {
+        // Tiles are drawn as unit squares around their centre, the longer side gets SIZE pixels
+        let scale = SIZE / ((max_x - min_x).max(max_y - min_y) + 1) as f64;
+        let sx = |x: f64| MARGIN + (x - min_x as f64 + 0.5) * scale;
+        let sy = |y: f64| MARGIN + (y - min_y as f64 + 0.5) * scale;
+        let (width, height) = (sx(max_x as f64 + 0.5) + MARGIN, sy(max_y as f64 + 0.5) + MARGIN);
 
-                // skip horizontal edges
-                if start_y == end_y { continue; }
+        let mut svg = format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{:.0}\" height=\"{:.0}\" viewBox=\"0 0 {:.1} {:.1}\">\n",
+            width, height, width, height
+        );
+        svg.push_str("<rect width=\"100%\" height=\"100%\" fill=\"white\"/>\n");
 
-                // top-inclusive
-                if start_y == y {
-                    active.push(Reverse((x, end_y)));
-                }
+        let mut path = String::new();
+        for outline in region.loops() {
+            for (i, p) in outline.vertices().iter().enumerate() {
+                path.push_str(&format!("{}{:.1},{:.1} ", if i == 0 { "M" } else { "L" }, sx(p.x() as f64), sy(p.y() as f64)));
             }
+            path.push_str("Z ");
+        }
+        svg.push_str(&format!(
+            "<path d=\"{}\" fill=\"#2e7d32\" fill-opacity=\"0.3\" fill-rule=\"evenodd\" stroke=\"#2e7d32\" stroke-width=\"1\"/>\n",
+            path.trim_end()
+        ));
 
-            // fill between pairs
-            let mut inside = false;
-            let mut last_x = 0;
+        let rect = |r: &CornerRect, colour: &str, best: bool| {
+            let (x1, x2) = (r.a.x().min(r.b.x()) as f64 - 0.5, r.a.x().max(r.b.x()) as f64 + 0.5);
+            let (y1, y2) = (r.a.y().min(r.b.y()) as f64 - 0.5, r.a.y().max(r.b.y()) as f64 + 0.5);
+            let style = if best {
+                format!("fill=\"{}\" fill-opacity=\"0.2\" stroke=\"{}\" stroke-width=\"3\"", colour, colour)
+            } else {
+                format!("fill=\"none\" stroke=\"{}\" stroke-opacity=\"0.6\" stroke-dasharray=\"6 4\"", colour)
+            };
+            format!(
+                "<rect x=\"{:.1}\" y=\"{:.1}\" width=\"{:.1}\" height=\"{:.1}\" {}><title>{} to {}: {}</title></rect>\n",
+                sx(x1), sy(y1), (x2 - x1) * scale, (y2 - y1) * scale, style, r.a, r.b, r.area
+            )
+        };
 
-            for &Reverse((x, _)) in &active {
-                if inside {
-                    for ix in last_x .. x + 1 {
-                        matrix[y as usize][ix as usize] = 1;
-                    }
-                }
-                inside = !inside;
-                last_x = x;
+        for (rects, colour) in [(part1, "#1565c0"), (part2, "#c62828")] {
+            for r in rects.iter().skip(1).rev() {
+                svg.push_str(&rect(r, colour, false));
+            }
+        }
+        for (rects, colour) in [(part1, "#1565c0"), (part2, "#c62828")] {
+            if let Some(best) = rects.first() {
+                svg.push_str(&rect(best, colour, true));
             }
         }
 
-        matrix
-    }
+        for p in region.vertices() {
+            svg.push_str(&format!(
+                "<circle cx=\"{:.1}\" cy=\"{:.1}\" r=\"{:.1}\" fill=\"#d32f2f\"/>\n",
+                sx(p.x() as f64), sy(p.y() as f64), (scale / 2.0).clamp(1.0, 4.0)
+            ));
+        }
 
+        let legend = [
+            ("#1565c0", "part 1", part1.first()),
+            ("#c62828", "part 2", part2.first()),
+        ];
+        for (row, (colour, label, best)) in legend.iter().enumerate() {
+            let area = best.map_or("none".to_string(), |r| r.area.to_string());
+            svg.push_str(&format!(
+                "<text x=\"{:.1}\" y=\"{:.1}\" font-family=\"monospace\" font-size=\"14\" fill=\"{}\">{}: {}</text>\n",
+                MARGIN, MARGIN + 16.0 * (row as f64 + 0.5), colour, label, area
+            ));
+        }
+
+        svg.push_str("</svg>\n");
+        svg
+    }
 }
 
 impl Day for Day09 {
-    // Brute force super simple
-    fn part1(&self, input: &str) -> String {
-        let points = Self::parse(input);
+    fn params(&self) -> Vec<Param> {
+        vec![
+            Param::new("search", "staircase", "part 1 search, brute (all pairs) or staircase (O(n log n))"),
+            Param::new("pixels", "120", "size of the longer side of the visualization"),
+            Param::new("runners_up", "5", "rectangles drawn behind the best one per part in the SVG export"),
+        ]
+    }
 
-        let mut max_area = 0;
+    fn validate(&self, input: &str, _params: &Params) -> Result<Vec<String>, String> {
+        let loops = Self::parse_loops(input)?;
+        let validations = loops.iter().map(|l| polygon::validate(&l.points)).collect::<Vec<Validation>>();
 
-        for i in 0..points.len() {
-            for j in i + 1..points.len() {
-                let area = (((points[i].x - points[j].x)).abs() + 1) * (((points[i].y - points[j].y)).abs() + 1);
-                if area > max_area {
-                    max_area = area;
+        let mut report = loops.iter().zip(&validations)
+            .flat_map(|(outline, validation)| Self::describe_defects(outline, validation))
+            .collect::<Vec<(bool, String)>>();
+
+        // Separate loops may nest but never touch
+        for a in 0..loops.len() {
+            for b in a + 1..loops.len() {
+                for (i, j) in polygon::crossings(&loops[a].points, &loops[b].points) {
+                    let (la, lb) = (&loops[a].lines, &loops[b].lines);
+                    report.push((true, format!(
+                        "Lines {}-{} and {}-{}: loops intersect",
+                        la[i], la[(i + 1) % la.len()], lb[j], lb[(j + 1) % lb.len()]
+                    )));
                 }
             }
         }
 
-        max_area.to_string()
+        if report.iter().any(|(error, _)| *error) {
+            return Err(report.into_iter().map(|(_, text)| text).collect::<Vec<String>>().join("\n"));
+        }
+
+        let region = Self::parse_region(input)?;
+        let mut notes = report.into_iter().map(|(_, text)| text).collect::<Vec<String>>();
+        for (i, (outline, validation)) in loops.iter().zip(&validations).enumerate() {
+            let winding = match validation.winding {
+                Some(Winding::Clockwise) => "runs clockwise (y pointing up)",
+                Some(Winding::CounterClockwise) => "runs counter clockwise (y pointing up)",
+                None => "encloses no area",
+            };
+            let role = if region.is_hole(i) { "is a hole and " } else { "" };
+            let shape = &region.loops()[i];
+            notes.push(format!(
+                "Loop on lines {}-{} {}{}, {} tiles on or inside it (perimeter {})",
+                outline.lines[0], outline.lines[outline.lines.len() - 1], role, winding,
+                shape.lattice_points(), shape.perimeter()
+            ));
+        }
+        notes.push(format!("{} red and green tiles in total", region.lattice_points()));
+        Ok(notes)
+    }
+
+    // SVG of the loops with the largest rectangles of both parts and the runners-up
+    fn export(&self, input: &str, params: &Params, path: &str) -> Result<(), String> {
+        let region = Self::parse_region(input)?;
+        let keep = params.get::<usize>("runners_up")? + 1;
+        let points = region.vertices().copied().collect::<Vec<Point2>>();
+
+        let loops = region.loops().iter().map(|l| l.vertices()).collect::<Vec<&[Point2]>>();
+        let polygon = RectilinearPolygon::from_loops(&loops);
+
+        let part1 = Self::ranked_rectangles(&points, keep, |_, _| true);
+        let part2 = Self::ranked_rectangles(&points, keep, |i, j| polygon.contains_rect(&points[i], &points[j]));
+
+        std::fs::write(path, Self::svg(&region, &part1, &part2)).map_err(|e| format!("Could not write {}: {}", path, e))?;
+        println!("Wrote polygon and rectangles to {}", path);
+        Ok(())
+    }
+
+    // The filled tiles on the compressed grid, scaled back to real proportions, with the part 2 rectangle
+    fn visualize(&self, input: &str, params: &Params, target: &str) -> Result<(), String> {
+        let region = Self::parse_region(input)?;
+        if region.loops().is_empty() {
+            return Err("There are no red tiles to draw".to_string());
+        }
+        let size = params.get::<usize>("pixels")?.max(1);
+        let mut sink = FrameSink::parse(target)?;
+
+        let points = region.vertices().copied().collect::<Vec<Point2>>();
+        let loops = region.loops().iter().map(|l| l.vertices()).collect::<Vec<&[Point2]>>();
+        let grid = CellGrid::rasterize(&loops);
+        let polygon = RectilinearPolygon::from_loops(&loops);
+        let best = Self::ranked_rectangles(&points, 1, |i, j| polygon.contains_rect(&points[i], &points[j]));
+
+        let caption = format!(
+            "{} red and green tiles on a {}x{} compressed grid, largest rectangle {}",
+            grid.filled_area(), grid.width(), grid.height(), best.first().map_or(0, |r| r.area)
+        );
+        sink.write(&Self::raster_frame(&grid, &points, best.first(), size), &caption)
     }
 
-    fn part2(&self, input: &str) -> String {
-        let points = Self::parse(input);
+    // Largest rectangle between any two red tiles, either by trying every pair or along the staircases
+    fn part1(&self, input: &str, params: &Params) -> String {
+        let points = match Self::parse(input) {
+            Ok(points) => points,
+            Err(err) => return err,
+        };
 
-        let coords: Vec<Coord<f64>> = points.iter().map(|p| Coord { x: p.x as f64, y: p.y as f64 }).collect();
-        let poly = Polygon::new(coords.clone().into(), vec![]);
+        let best = match params.str("search") {
+            "brute" => corner_rectangles::largest_brute_force(&points),
+            "staircase" => corner_rectangles::largest_staircase(&points),
+            other => return format!("Unknown search '{}', expected brute or staircase", other),
+        };
+
+        best.map_or(0, |rect| rect.area).to_string()
+    }
+
+    fn part2(&self, input: &str, _params: &Params) -> String {
+        let region = match Self::parse_region(input) {
+            Ok(region) => region,
+            Err(err) => return err,
+        };
+        let points = region.vertices().copied().collect::<Vec<Point2>>();
+
+        // Rasterise all loops once on the compressed grid, holes included, then every rectangle check is O(1)
+        let loops = region.loops().iter().map(|l| l.vertices()).collect::<Vec<&[Point2]>>();
+        let polygon = RectilinearPolygon::from_loops(&loops);
+        let compressed = points.iter().map(|p| polygon.compress(p).unwrap()).collect::<Vec<(usize, usize)>>();
 
         let mut max_area = 0;
 
         for i in 0..points.len() {
             for j in i + 1..points.len() {
-                let min_x = points[i].x.min(points[j].x);
-                let max_x = points[i].x.max(points[j].x);
-                let min_y = points[i].y.min(points[j].y);
-                let max_y = points[i].y.max(points[j].y);
-
-                let rect = Rect::new(
-                    Coord { x: min_x as f64, y: min_y as f64 },
-                    Coord { x: max_x as f64, y: max_y as f64 },
-                );
-
-                // Convert rect to polygon for contains check
-                let rect_poly = Polygon::new(rect.to_polygon().exterior().clone(), vec![]);
-
-                if poly.contains(&rect_poly) {
-                    let area = ((max_x - min_x + 1) * (max_y - min_y + 1)) as usize;
-                    if area > max_area {
-                        max_area = area;
-                    }
+                let area = ((points[i].x() - points[j].x()).abs() + 1) * ((points[i].y() - points[j].y()).abs() + 1);
+
+                if area > max_area && polygon.contains_compressed_rect(compressed[i], compressed[j]) {
+                    max_area = area;
                 }
             }
         }
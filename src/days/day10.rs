@@ -1,51 +1,330 @@
-use std::vec;
-
-use super::Day;
+use super::{Day, Param, Params};
 use regex::Regex;
-use good_lp::{Expression, Solution, SolverModel, Variable, constraint, default_solver, microlp, solvers::highs, variable, variables};
+use std::time::{Duration, Instant};
+use crate::utils::gf2::{BitMatrix, BitVec};
+use crate::utils::integer_program::{Backend, IntegerProgram, Outcome};
+use crate::utils::model_file::{Format, Model};
+use crate::utils::worker_pool;
 
 pub struct Day10;
 
+// One line of the manual: the indicator light pattern, the buttons with the lights (and joltage
+// counters) they are wired to, and the joltage requirements.
+// Variant puzzles add to that: a cost per press and a press limit per button, written right after
+// the button as (0,2)$3<=4, and |k| when exactly k distinct buttons have to be pressed.
+pub struct Machine {
+    pub lights: Vec<bool>,
+    pub buttons: Vec<Vec<usize>>,
+    // Bit i is set if the button toggles light i
+    pub masks: Vec<u64>,
+    pub costs: Vec<i64>,
+    pub limits: Vec<Option<i64>>,
+    pub distinct: Option<usize>,
+    pub joltage: Vec<i64>,
+}
+
+// A button as written in the manual, cost 1 and no limit unless given
+struct ButtonSpec {
+    lights: Vec<usize>,
+    cost: i64,
+    limit: Option<i64>,
+}
+
+// What a machine shows after a press vector, starting with every light off and every counter at 0
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MachineState {
+    pub lights: Vec<bool>,
+    pub joltage: Vec<i64>,
+}
+
+impl Machine {
+    fn new(lights: Vec<bool>, specs: Vec<ButtonSpec>, distinct: Option<usize>, joltage: Vec<i64>) -> Result<Self, String> {
+        if lights.len() > 64 {
+            return Err(format!("{} lights don't fit into a 64 bit mask", lights.len()));
+        }
+        if joltage.len() != lights.len() {
+            return Err(format!("{} lights but {} joltage requirements", lights.len(), joltage.len()));
+        }
+        if let Some(&light) = specs.iter().flat_map(|spec| &spec.lights).find(|&&light| light >= lights.len()) {
+            return Err(format!("Button wired to light {} but there are only {}", light, lights.len()));
+        }
+
+        let costs = specs.iter().map(|spec| spec.cost).collect();
+        let limits = specs.iter().map(|spec| spec.limit).collect();
+        let buttons = specs.into_iter().map(|spec| spec.lights).collect::<Vec<Vec<usize>>>();
+        let masks = buttons.iter().map(|button| button.iter().fold(0u64, |mask, &light| mask | 1 << light)).collect();
+        Ok(Self { lights, buttons, masks, costs, limits, distinct, joltage })
+    }
+
+    // Presses button j presses[j] times. Every press toggles its lights and bumps its counters by one.
+    pub fn simulate(&self, presses: &[i64]) -> MachineState {
+        let mut mask = 0u64;
+        let mut joltage = vec![0; self.joltage.len()];
+        for (j, &count) in presses.iter().enumerate() {
+            if count % 2 == 1 {
+                mask ^= self.masks[j];
+            }
+            for &counter in &self.buttons[j] {
+                joltage[counter] += count;
+            }
+        }
+        let lights = (0..self.lights.len()).map(|i| mask >> i & 1 == 1).collect();
+        MachineState { lights, joltage }
+    }
+
+    fn check_presses(&self, presses: &[i64]) -> Result<(), String> {
+        if presses.len() != self.buttons.len() {
+            return Err(format!("{} presses for {} buttons", presses.len(), self.buttons.len()));
+        }
+        if let Some(count) = presses.iter().find(|&&count| count < 0) {
+            return Err(format!("negative press count {}", count));
+        }
+        if let Some(j) = (0..presses.len()).find(|&j| self.limits[j].is_some_and(|limit| presses[j] > limit)) {
+            return Err(format!("button {} pressed {} times but its limit is {}", j, presses[j], self.limits[j].unwrap()));
+        }
+        if let Some(k) = self.distinct {
+            let used = presses.iter().filter(|&&count| count > 0).count();
+            if used != k {
+                return Err(format!("{} distinct buttons pressed instead of {}", used, k));
+            }
+        }
+        Ok(())
+    }
+
+    pub fn cost(&self, presses: &[i64]) -> i64 {
+        presses.iter().zip(&self.costs).map(|(p, c)| p * c).sum()
+    }
+
+    fn usable(&self, j: usize) -> bool {
+        self.limits[j] != Some(0)
+    }
+
+    // Does the press vector light up exactly the indicator pattern
+    pub fn verify_lights(&self, presses: &[i64]) -> Result<(), String> {
+        self.check_presses(presses)?;
+        let state = self.simulate(presses);
+        if state.lights != self.lights {
+            return Err(format!("lights end up as {} instead of {}", Self::show_lights(&state.lights), Self::show_lights(&self.lights)));
+        }
+        Ok(())
+    }
+
+    // Does the press vector hit every joltage requirement exactly
+    pub fn verify_joltage(&self, presses: &[i64]) -> Result<(), String> {
+        self.check_presses(presses)?;
+        let state = self.simulate(presses);
+        if state.joltage != self.joltage {
+            return Err(format!("joltage ends up as {:?} instead of {:?}", state.joltage, self.joltage));
+        }
+        Ok(())
+    }
+
+    // The obvious reasons a machine can't be solved, found before any solver runs
+    fn unreachable_light(&self) -> Option<String> {
+        let light = (0..self.lights.len()).find(|&i| self.lights[i] && !(0..self.buttons.len()).any(|j| self.usable(j) && self.buttons[j].contains(&i)))?;
+        Some(format!("light {} has to be on but no button is wired to it", light))
+    }
+
+    fn unreachable_counter(&self) -> Option<String> {
+        let counter = (0..self.joltage.len()).find(|&i| self.joltage[i] > 0 && !(0..self.buttons.len()).any(|j| self.usable(j) && self.buttons[j].contains(&i)))?;
+        Some(format!("joltage counter {} needs {} but no button is wired to it", counter, self.joltage[counter]))
+    }
+
+    fn show_lights(lights: &[bool]) -> String {
+        lights.iter().map(|&on| if on { '#' } else { '.' }).collect()
+    }
+
+    // One row per light, one column per button
+    fn light_system(&self) -> (BitMatrix, BitVec) {
+        let mut matrix = BitMatrix::zeros(self.lights.len(), self.buttons.len());
+        for (j, button) in self.buttons.iter().enumerate() {
+            for &light in button {
+                matrix.set(light, j, true);
+            }
+        }
+        (matrix, BitVec::from_bits(self.lights.iter().copied()))
+    }
+
+    // Presses for a solution of the light system, None if the limits rule it out. Costs are never
+    // negative, so pressing a button more than twice is never cheaper; a second press only helps when
+    // more distinct buttons are needed, and then the cheapest unused ones get pressed twice.
+    fn light_presses(&self, bits: &BitVec) -> Option<Vec<i64>> {
+        let mut presses = (0..self.buttons.len()).map(|j| bits.get(j) as i64).collect::<Vec<i64>>();
+        if bits.ones().any(|j| !self.usable(j)) {
+            return None;
+        }
+
+        if let Some(k) = self.distinct {
+            let used = bits.count_ones();
+            let mut spare = (0..self.buttons.len())
+                .filter(|&j| !bits.get(j) && self.limits[j].is_none_or(|limit| limit >= 2))
+                .collect::<Vec<usize>>();
+            if used > k || used + spare.len() < k {
+                return None;
+            }
+            spare.sort_by_key(|&j| self.costs[j]);
+            for &j in &spare[..k - used] {
+                presses[j] = 2;
+            }
+        }
+        Some(presses)
+    }
+
+    // One equation per joltage counter: the presses of all buttons wired to it add up to the target.
+    // No button can be pressed more often than the smallest counter it is wired to allows.
+    fn joltage_program(&self) -> IntegerProgram {
+        let matrix = (0..self.joltage.len())
+            .map(|i| self.buttons.iter().map(|button| button.contains(&i) as i64).collect())
+            .collect::<Vec<Vec<i64>>>();
+        let upper = self.buttons.iter().zip(&self.limits).map(|(button, &limit)| {
+            let wired = button.iter().map(|&i| self.joltage[i]).min();
+            match (wired, limit) {
+                (Some(wired), Some(limit)) => Some(wired.min(limit)),
+                (wired, limit) => wired.or(limit),
+            }
+        }).collect();
+
+        let program = IntegerProgram::new(matrix, self.joltage.clone())
+            .with_costs(self.costs.clone())
+            .with_upper_bounds(upper);
+        match self.distinct {
+            Some(k) => program.with_distinct(k),
+            None => program,
+        }
+    }
+}
 
 impl Day10 {
-    fn parse(input: &str) -> Vec<(Vec<u8>, Vec<Vec<i64>>, Vec<i64>)> {
+    fn parse(input: &str) -> Result<Vec<Machine>, String> {
         let square_re = Regex::new(r"(\[[^\]\r\n]+\])")
             .expect("Invalid regex");
-        let round_re = Regex::new(r"(\([^\)\r\n]+\))")
+        let round_re = Regex::new(r"\(([^\)\r\n]+)\)(?:\$(\d+))?(?:<=(\d+))?")
+            .expect("Invalid regex");
+        let distinct_re = Regex::new(r"\|(\d+)\|")
             .expect("Invalid regex");
         let wavey_re = Regex::new(r"(\{[^}\r\n]+\})")
             .expect("Invalid regex");
 
-        input.lines().map(|row| {
-            let square = square_re.captures(row).unwrap()[1]
+        input.lines().enumerate().map(|(idx, row)| {
+            let number = |x: &str| x.trim().parse::<i64>().map_err(|_| format!("Line {}: invalid number '{}'", idx + 1, x));
+
+            let square = square_re.captures(row).ok_or(format!("Line {}: no indicator lights in [...]", idx + 1))?[1]
                 .chars()
                 .filter(|&c| c != '[' && c != ']')
                 .map(|c| match c {
-                    '.' => 0,
-                    '#' => 1,
-                    _ => panic!("Unexpected character in square brackets"),
+                    '.' => Ok(false),
+                    '#' => Ok(true),
+                    _ => Err(format!("Line {}: unexpected character '{}' in square brackets", idx + 1, c)),
                 })
-                .collect::<Vec<u8>>();
-            let round = round_re.find_iter(row)
-                .map(|m| {
-                    m.as_str()
-                    .trim_matches(&['(', ')'][..])
-                    .split(',')
-                    .map(|x| x.parse::<i64>().unwrap())
-                    .collect::<Vec<i64>>()
+                .collect::<Result<Vec<bool>, String>>()?;
+            let round = round_re.captures_iter(row)
+                .map(|cap| {
+                    let lights = cap[1]
+                        .split(',')
+                        .map(|x| number(x).map(|x| x as usize))
+                        .collect::<Result<Vec<usize>, String>>()?;
+                    let cost = cap.get(2).map_or(Ok(1), |m| number(m.as_str()))?;
+                    let limit = cap.get(3).map(|m| number(m.as_str())).transpose()?;
+                    Ok(ButtonSpec { lights, cost, limit })
                 })
-                .collect();
+                .collect::<Result<Vec<ButtonSpec>, String>>()?;
+            let distinct = distinct_re.captures(row)
+                .map(|cap| number(&cap[1]).map(|k| k as usize))
+                .transpose()?;
             let wavey = wavey_re.captures(row)
                 .map(|cap| {
                     cap[1][1..cap[1].len()-1] // Slice the matched substring, not the entire row
                         .split(",")
-                        .map(|x| x.parse::<i64>().unwrap())
-                        .collect::<Vec<i64>>()
-                }).unwrap();
-            (square, round, wavey)
+                        .map(number)
+                        .collect::<Result<Vec<i64>, String>>()
+                })
+                .ok_or(format!("Line {}: no joltage requirements in {{...}}", idx + 1))??;
+
+            Machine::new(square, round, distinct, wavey).map_err(|err| format!("Line {}: {}", idx + 1, err))
         }).collect()
     }
 
+    fn check_backend(params: &Params) -> Result<Option<Backend>, String> {
+        match params.str("check") {
+            "none" | "" => Ok(None),
+            _ => params.get::<Backend>("check").map(Some),
+        }
+    }
+
+    fn thread_count(params: &Params) -> Result<usize, String> {
+        match params.str("threads") {
+            "auto" | "" => Ok(worker_pool::available_threads()),
+            _ => params.get::<usize>("threads").map(|n| n.max(1)),
+        }
+    }
+
+    // Part 2 for one machine, how long the main solver took for it, and with a second backend what
+    // the two disagree about if they do
+    fn solve_joltage(machine: &Machine, solver: Backend, check: Option<Backend>) -> Result<(Outcome, Duration, Option<String>), String> {
+        let start = Instant::now();
+        if let Some(reason) = machine.unreachable_counter() {
+            return Ok((Outcome::Infeasible(reason), start.elapsed(), None));
+        }
+
+        // Whatever the backend says, the simulator has the last word
+        let program = machine.joltage_program();
+        let outcome = Self::verified(program.solve_with(solver)?, |p| machine.verify_joltage(p));
+        let elapsed = start.elapsed();
+
+        // Cross-check: the same machine with the second backend, the sum alone hides which one differs
+        let Some(check) = check else {
+            return Ok((outcome, elapsed, None));
+        };
+        let other = Self::verified(program.solve_with(check)?, |p| machine.verify_joltage(p));
+        let summary = |o: &Outcome| match o {
+            Outcome::Optimal(presses) => machine.cost(presses).to_string(),
+            Outcome::Infeasible(_) => "infeasible".to_string(),
+            Outcome::Unbounded => "unbounded".to_string(),
+        };
+        let disagreement = (summary(&outcome) != summary(&other))
+            .then(|| format!("{} {} vs {} {}", solver, summary(&outcome), check, summary(&other)));
+        Ok((outcome, elapsed, disagreement))
+    }
+
+    // Total cost over the solved machines, then every failure (and with presses=true every cost and
+    // press vector) by line. With the default cost of 1 per press the cost is the number of presses.
+    fn report(machines: &[Machine], outcomes: &[Outcome], show_presses: bool, mut notes: Vec<String>) -> String {
+        let mut sum = 0;
+        let mut solved = 0;
+        for (idx, (machine, outcome)) in machines.iter().zip(outcomes).enumerate() {
+            match outcome {
+                Outcome::Optimal(presses) => {
+                    sum += machine.cost(presses);
+                    solved += 1;
+                    if show_presses {
+                        notes.push(format!("line {}: cost {} with {:?}", idx + 1, machine.cost(presses), presses));
+                    }
+                }
+                Outcome::Infeasible(reason) => notes.push(format!("line {}: infeasible, {}", idx + 1, reason)),
+                Outcome::Unbounded => notes.push(format!("line {}: unbounded", idx + 1)),
+            }
+        }
+
+        if solved < outcomes.len() {
+            notes.insert(0, format!("{} of {} machines solved", solved, outcomes.len()));
+        }
+        if notes.is_empty() {
+            sum.to_string()
+        } else {
+            format!("{}\n  {}", sum, notes.join("\n  "))
+        }
+    }
+
+    // Turns a solver answer the simulator disagrees with into a failure
+    fn verified(outcome: Outcome, verify: impl Fn(&[i64]) -> Result<(), String>) -> Outcome {
+        match outcome {
+            Outcome::Optimal(presses) => match verify(&presses) {
+                Ok(()) => Outcome::Optimal(presses),
+                Err(err) => Outcome::Infeasible(format!("solver answer {:?} fails verification, {}", presses, err)),
+            },
+            other => other,
+        }
+    }
 
     // Failed DP branch attempt
     fn dp_step(buttons: &Vec<Vec<i64>>, start_state: Vec<i64>, target: &Vec<i64>, memo: &mut std::collections::HashMap<Vec<i64>, i64>, depth: i64) -> i64 {
@@ -74,88 +353,133 @@ impl Day10 {
 }
 
 impl Day for Day10 {
-    fn part1(&self, input: &str) -> String {
-        let data = Self::parse(input);
-        let mut sum = 0;
+    fn params(&self) -> Vec<Param> {
+        vec![
+            Param::new("solver", "native", "part 2 solver: native (exact), microlp or highs"),
+            Param::new("check", "none", "second solver to cross-check part 2 against, machine by machine"),
+            Param::new("presses", "false", "true to list the optimal cost and press vector of every machine"),
+            Param::new("format", "lp", "model file format for --export: lp or mps"),
+            Param::new("threads", "auto", "worker threads for part 2, auto is one per core"),
+        ]
+    }
 
-        for row in data.iter() {
-            let target = &row.0;
-            let buttons = &row.1;
+    // One part 2 model per machine into the directory at path, line003.lp for the machine on line 3
+    fn export(&self, input: &str, params: &Params, path: &str) -> Result<(), String> {
+        let format = params.get::<Format>("format")?;
+        let machines = Self::parse(input)?;
+        std::fs::create_dir_all(path).map_err(|e| format!("Could not create {}: {}", path, e))?;
 
-            // We need to see that pushing any button twice is the same as pushing it zero times
-            // this is so because pushing the button a second time will just revert the first push exactly
-            // As such we generate all combinations of button presses where each button is either pressed 0 or 1 time.
-            let num_buttons = buttons.len();
-            let mut combinations = vec![];
-
-            for mask in 1..(1 << num_buttons) {
-                let mut combo = Vec::new();
-                for i in 0..num_buttons {
-                    if (mask & (1 << i)) != 0 {
-                        combo.push(i + 1);
-                    }
-                }
-                combinations.push(combo);
-            }
+        for (idx, machine) in machines.iter().enumerate() {
+            let model = Model::from_program(&machine.joltage_program()).map_err(|err| format!("Line {}: {}", idx + 1, err))?;
+            let file = format!("{}/line{:03}.{}", path.trim_end_matches('/'), idx + 1, format.extension());
+            let text = model.write(format, &format!("Day 10 machine on line {}", idx + 1));
+            std::fs::write(&file, text).map_err(|e| format!("Could not write {}: {}", file, e))?;
+        }
+        println!("Wrote {} models to {}", machines.len(), path);
+        Ok(())
+    }
 
-            combinations.sort_by(|a, b| a.len().cmp(&b.len()));
+    // Solves a model file (ours or from anywhere else) with the configured solver
+    fn import(&self, params: &Params, path: &str) -> Result<(), String> {
+        let solver = params.get::<Backend>("solver")?;
+        let text = std::fs::read_to_string(path).map_err(|e| format!("Could not read {}: {}", path, e))?;
+        let model = Model::read(Format::of_path(path)?, &text).map_err(|err| format!("{}: {}", path, err))?;
 
-            for combo in &combinations {
-                let mut start = vec![0; target.len()];
-                for &button_index in combo {
-                    let button = &buttons[button_index - 1];
-                    for &pos in button {
-                        start[pos as usize] ^= 1;
-                    }
-                }
-                if start == *target {
-                    sum += combo.len();
-                    break;
+        match model.to_program().solve_with(solver)? {
+            Outcome::Optimal(x) => {
+                let cost = model.costs.iter().zip(&x).map(|(c, v)| c * v).sum::<i64>();
+                println!("{}: cost {} with {}", path, cost, solver);
+                for (name, value) in model.names.iter().zip(&x) {
+                    println!("  {} = {}", name, value);
                 }
             }
+            Outcome::Infeasible(reason) => println!("{}: infeasible, {}", path, reason),
+            Outcome::Unbounded => println!("{}: unbounded", path),
         }
-
-        sum.to_string()
+        Ok(())
     }
 
-    fn part2(&self, input: &str) -> String {
-        // This is a linear programming problem! Min sum = min(x_i) where x_i is the number of times a button is pressed
-        // this probably works for part 1 too i assume but I didnt test.
-        let data = Self::parse(input);
-        let mut sum = 0;
+    fn part1(&self, input: &str, params: &Params) -> String {
+        let machines = match Self::parse(input) {
+            Ok(machines) => machines,
+            Err(err) => return err,
+        };
 
-        for row in data.iter() {
-            let joltage_targets = &row.2;
-            let buttons = &row.1;
+        let outcomes = machines.iter().map(|machine| {
+            if let Some(reason) = machine.unreachable_light() {
+                return Outcome::Infeasible(reason);
+            }
 
-            let mut vars = variables!();
+            // We need to see that pushing any button twice is the same as pushing it zero times
+            // this is so because pushing the button a second time will just revert the first push exactly
+            // So every button is pressed 0 or 1 times and the lights are a linear system over GF(2):
+            // one row per light, one column per button, and we want the cheapest solution
+            let (matrix, lights) = machine.light_system();
+            let Some(space) = matrix.solve(&lights) else {
+                return Outcome::Infeasible("no combination of buttons gives this light pattern".to_string());
+            };
 
-            // Create variables for all of our buttons
-            let x: Vec<Variable> = (0..buttons.len())
-                .map(|_| vars.add(variable().integer().min(0)))
-                .collect();
+            let cheapest = match space.cheapest(|bits| machine.light_presses(bits).map(|p| machine.cost(&p))) {
+                Ok(cheapest) => cheapest,
+                Err(err) => return Outcome::Infeasible(err),
+            };
+            let Some(presses) = cheapest.and_then(|bits| machine.light_presses(&bits)) else {
+                return Outcome::Infeasible("the light pattern needs buttons the press limits rule out".to_string());
+            };
+            Self::verified(Outcome::Optimal(presses), |p| machine.verify_lights(p))
+        }).collect::<Vec<Outcome>>();
 
-            // The problem we try to minimize is the sum of our button presses
-            let total_presses = x.iter().sum::<good_lp::Expression>();
-            let mut problem = vars.minimise(total_presses).using(good_lp::highs);
+        Self::report(&machines, &outcomes, params.str("presses") == "true", vec![])
+    }
 
-            // For each joltage target we create a constraint that is the sum of coefficient * x_i 
-            for i in 0..joltage_targets.len() {
-                let mut expr = good_lp::Expression::from(0.0);
-                for (j, button) in buttons.iter().enumerate() {
-                    if button.contains(&(i as i64)) {
-                        expr = expr + x[j];
-                    }
-                }
-                // Add equality constraint
-                problem = problem.with(expr.eq(joltage_targets[i] as f64));
-            }
+    fn part2(&self, input: &str, params: &Params) -> String {
+        // This is an integer linear program! Min sum = min(c_i x_i) where x_i is the number of times a button is pressed
+        // and c_i its cost, subject to every joltage counter getting exactly its target.
+        let (solver, check) = match (params.get::<Backend>("solver"), Self::check_backend(params)) {
+            (Ok(solver), Ok(check)) => (solver, check),
+            (Err(err), _) | (_, Err(err)) => return err,
+        };
+        let machines = match Self::parse(input) {
+            Ok(machines) => machines,
+            Err(err) => return err,
+        };
 
-            // Solve it and evaluate solution
-            let solution = problem.solve().expect("Solver failed");
-            sum += solution.eval(&x.iter().sum::<good_lp::Expression>()) as i64
+        let threads = match Self::thread_count(params) {
+            Ok(threads) => threads,
+            Err(err) => return err,
+        };
+
+        // The lines are independent, so they go to a worker pool
+        let solved = worker_pool::map(&machines, threads, |_, machine| Self::solve_joltage(machine, solver, check));
+
+        let mut outcomes = vec![];
+        let mut notes = vec![];
+        let mut slowest: Option<(usize, Duration)> = None;
+        for (idx, result) in solved.into_iter().enumerate() {
+            let (outcome, elapsed, disagreement) = match result {
+                Ok(solved) => solved,
+                Err(err) => return format!("Line {}: {}", idx + 1, err),
+            };
+            if let Some(disagreement) = disagreement {
+                notes.push(format!("line {}: {}", idx + 1, disagreement));
+            }
+            if slowest.is_none_or(|(_, longest)| elapsed > longest) {
+                slowest = Some((idx, elapsed));
+            }
+            outcomes.push(outcome);
         }
 
-        sum.to_string()
+        if let Some(check) = check {
+            let verdict = match notes.len() {
+                0 => format!("{} and {} agree on every machine", solver, check),
+                n => format!("{} and {} disagree on {} machines", solver, check, n),
+            };
+            notes.insert(0, verdict);
+        }
+        // Timings change from run to run, so they stay out of the answer
+        if let Some((idx, elapsed)) = slowest {
+            println!("Slowest machine: line {} took {:.2?} with {} (threads: {})", idx + 1, elapsed, solver, threads);
+        }
+        Self::report(&machines, &outcomes, params.str("presses") == "true", notes)
     }
 }
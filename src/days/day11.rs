@@ -2,9 +2,7 @@ use std::cmp::Reverse;
 use std::collections::{BinaryHeap, HashMap, HashSet};
 use std::vec;
 
-use super::Day;
-use good_lp::{Expression, Solution, SolverModel, Variable, constraint, default_solver, microlp, solvers::highs, variable, variables};
-use regex::Regex;
+use super::{Day, Param, Params};
 
 pub struct Day11;
 
@@ -29,7 +27,7 @@ impl Graph {
 }
 
 impl Day11 {
-    fn parse(input: &str) -> (usize, usize, usize, usize, usize, Graph) {
+    fn parse(input: &str, out: &str) -> (HashMap<String, usize>, Graph) {
         let mut graph = Graph::new(input.lines().count() + 1); // +1 for "out" node
         let mut node_indices = std::collections::HashMap::new();
         let mut idx = 0;
@@ -47,7 +45,7 @@ impl Day11 {
         }
 
         // Add "out" node
-        node_indices.entry("out".to_string()).or_insert_with(|| {
+        node_indices.entry(out.to_string()).or_insert_with(|| {
             let i = idx;
             idx += 1;
             i
@@ -66,7 +64,17 @@ impl Day11 {
         }
 
 
-        (node_indices["you"], node_indices["out"], node_indices["svr"], node_indices["dac"], node_indices["fft"],  graph)
+        (node_indices, graph)
+    }
+
+    // Looks up the node names given as params
+    fn nodes<const N: usize>(node_indices: &HashMap<String, usize>, params: &Params, keys: [&str; N]) -> Result<[usize; N], String> {
+        let mut nodes = [0; N];
+        for (node, key) in nodes.iter_mut().zip(keys) {
+            let name = params.str(key);
+            *node = *node_indices.get(name).ok_or_else(|| format!("Node '{}' ({}) is not in the input", name, key))?;
+        }
+        Ok(nodes)
     }
 
     fn Astar(start: usize, target: usize, graph: &Graph) -> Option<Vec<usize>> {
@@ -153,15 +161,33 @@ impl Day11 {
 }
 
 impl Day for Day11 {
-    fn part1(&self, input: &str) -> String {
-        let (start, target, _, _, _, graph) = Self::parse(input);
+    fn params(&self) -> Vec<Param> {
+        vec![
+            Param::new("start", "you", "node the part 1 paths start at"),
+            Param::new("server", "svr", "node the part 2 paths start at"),
+            Param::new("dac", "dac", "node every part 2 path has to visit (either order)"),
+            Param::new("fft", "fft", "node every part 2 path has to visit (either order)"),
+            Param::new("out", "out", "node all paths end at"),
+        ]
+    }
+
+    fn part1(&self, input: &str, params: &Params) -> String {
+        let (node_indices, graph) = Self::parse(input, params.str("out"));
+        let [start, target] = match Self::nodes(&node_indices, params, ["start", "out"]) {
+            Ok(nodes) => nodes,
+            Err(err) => return err,
+        };
         let mut memo = HashMap::new();
         let count = Self::count_all_paths_with_memo(&graph, start, target, &mut memo);
         count.to_string()
     }
 
-    fn part2(&self, input: &str) -> String {
-        let (_, out, svr, dac, fft, graph) = Self::parse(input);
+    fn part2(&self, input: &str, params: &Params) -> String {
+        let (node_indices, graph) = Self::parse(input, params.str("out"));
+        let [svr, dac, fft, out] = match Self::nodes(&node_indices, params, ["server", "dac", "fft", "out"]) {
+            Ok(nodes) => nodes,
+            Err(err) => return err,
+        };
         let mut memo = HashMap::new();
 
         let paths_svr_to_dac = Self::count_all_paths_with_memo(&graph, svr, dac, &mut memo);
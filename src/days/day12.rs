@@ -1,7 +1,7 @@
 pub struct Day12;
 use std::vec;
 
-use super::Day;
+use super::{Day, Params};
 
 
 impl Day12 {
@@ -65,7 +65,7 @@ impl Day12 {
 }
 
 impl Day for Day12 {
-    fn part1(&self, input: &str) -> String {
+    fn part1(&self, input: &str, _params: &Params) -> String {
         let (gifts, trees) = Self::parse(input);
 
         let mut can_fit_maybe = 0;
@@ -89,7 +89,7 @@ impl Day for Day12 {
         can_fit_maybe.to_string()
     }
 
-    fn part2(&self, input: &str) -> String {
+    fn part2(&self, input: &str, _params: &Params) -> String {
         "We did it wahoo".to_string()
     }
 }
@@ -1,8 +1,35 @@
 pub trait Day {
-    fn part1(&self, input: &str) -> String;
-    fn part2(&self, input: &str) -> String;
+    fn part1(&self, input: &str, params: &Params) -> String;
+    fn part2(&self, input: &str, params: &Params) -> String;
+
+    // Puzzle constants that can be changed with --param name=value, with their defaults
+    fn params(&self) -> Vec<Param> {
+        vec![]
+    }
+
+    // Checks the input before anything gets solved. Ok carries notes worth printing,
+    // Err a report of everything that is wrong with the input.
+    fn validate(&self, _input: &str, _params: &Params) -> Result<Vec<String>, String> {
+        Ok(vec![])
+    }
+
+    // Optional visual output of how a day gets solved, target is e.g. "term" or "ppm:frames"
+    fn visualize(&self, _input: &str, _params: &Params, _target: &str) -> Result<(), String> {
+        Err("This day has no visualization yet".to_string())
+    }
+
+    // Writes analysis data of a day to a file, e.g. the Day 8 percolation curve as CSV
+    fn export(&self, _input: &str, _params: &Params, _path: &str) -> Result<(), String> {
+        Err("This day has nothing to export yet".to_string())
+    }
+
+    // Reads back a file written by export and works on it instead of the puzzle input
+    fn import(&self, _params: &Params, _path: &str) -> Result<(), String> {
+        Err("This day has nothing to import yet".to_string())
+    }
 }
 
+pub mod params;
 pub mod day01;
 pub mod day02;
 pub mod day03;
@@ -18,6 +45,8 @@ pub mod day12;
 
 use std::vec;
 
+pub use params::{Param, Params};
+
 use day01::Day01;
 use day02::Day02;
 use day03::Day03;
@@ -0,0 +1,67 @@
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::str::FromStr;
+
+// A puzzle constant a day lets you override with --param name=value
+pub struct Param {
+    pub name: &'static str,
+    pub default: &'static str,
+    pub help: &'static str,
+}
+
+impl Param {
+    pub const fn new(name: &'static str, default: &'static str, help: &'static str) -> Self {
+        Self { name, default, help }
+    }
+}
+
+// The declared defaults of a day with the command line overrides applied
+#[derive(Debug, Clone, Default)]
+pub struct Params {
+    values: HashMap<String, String>,
+}
+
+impl Params {
+    pub fn new(declared: &[Param], overrides: &[(String, String)]) -> Result<Self, String> {
+        let mut values = declared.iter()
+            .map(|p| (p.name.to_string(), p.default.to_string()))
+            .collect::<HashMap<String, String>>();
+
+        for (key, value) in overrides {
+            match values.get_mut(key) {
+                Some(slot) => *slot = value.clone(),
+                None => {
+                    if declared.is_empty() {
+                        return Err(format!("Unknown parameter '{}', this day has no parameters", key));
+                    }
+                    let known = declared.iter()
+                        .map(|p| format!("  {} = {} ({})", p.name, p.default, p.help))
+                        .collect::<Vec<String>>();
+                    return Err(format!("Unknown parameter '{}', available are:\n{}", key, known.join("\n")));
+                }
+            }
+        }
+
+        Ok(Self { values })
+    }
+
+    // Parses a "key=value" command line argument
+    pub fn parse_override(arg: &str) -> Result<(String, String), String> {
+        match arg.split_once('=') {
+            Some((key, value)) if !key.trim().is_empty() => Ok((key.trim().to_string(), value.trim().to_string())),
+            _ => Err(format!("Invalid parameter '{}', expected key=value", arg)),
+        }
+    }
+
+    pub fn str(&self, key: &str) -> &str {
+        self.values.get(key).map_or("", |v| v.as_str())
+    }
+
+    pub fn get<T: FromStr>(&self, key: &str) -> Result<T, String>
+    where
+        T::Err: Display,
+    {
+        let value = self.values.get(key).ok_or_else(|| format!("Parameter '{}' is not declared", key))?;
+        value.parse::<T>().map_err(|err| format!("Invalid value '{}' for parameter '{}': {}", value, key, err))
+    }
+}
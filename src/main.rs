@@ -1,4 +1,5 @@
 mod days;
+mod utils;
 
 use std::env;
 use std::io::{self, Write};
@@ -8,6 +9,10 @@ fn main() {
     let args: Vec<String> = env::args().collect();
     let mut day_arg: Option<u32> = None;
     let mut num_runs: u32 = 1;
+    let mut visualize: Option<String> = None;
+    let mut export: Option<String> = None;
+    let mut import: Option<String> = None;
+    let mut overrides: Vec<(String, String)> = vec![];
 
     // Parse command-line arguments
     let mut i = 1;
@@ -31,6 +36,68 @@ fn main() {
                     return;
                 }
             }
+            "-v" | "--visualize" => {
+                if i + 1 < args.len() {
+                    visualize = Some(args[i + 1].clone());
+                    i += 1;
+                } else {
+                    eprintln!("Error: --visualize requires a target (term or ppm:<dir>)");
+                    return;
+                }
+            }
+            "-e" | "--export" => {
+                if i + 1 < args.len() {
+                    export = Some(args[i + 1].clone());
+                    i += 1;
+                } else {
+                    eprintln!("Error: --export requires an output path");
+                    return;
+                }
+            }
+            "-i" | "--import" => {
+                if i + 1 < args.len() {
+                    import = Some(args[i + 1].clone());
+                    i += 1;
+                } else {
+                    eprintln!("Error: --import requires a file to read");
+                    return;
+                }
+            }
+            "-p" | "--param" => {
+                if i + 1 < args.len() {
+                    match days::Params::parse_override(&args[i + 1]) {
+                        Ok(kv) => overrides.push(kv),
+                        Err(err) => {
+                            eprintln!("Error: {}", err);
+                            return;
+                        }
+                    }
+                    i += 1;
+                } else {
+                    eprintln!("Error: --param requires key=value");
+                    return;
+                }
+            }
+            // Shorthand for --param metric=...
+            "--metric" => {
+                if i + 1 < args.len() {
+                    overrides.push(("metric".to_string(), args[i + 1].clone()));
+                    i += 1;
+                } else {
+                    eprintln!("Error: --metric requires a metric (euclidean, manhattan or chebyshev)");
+                    return;
+                }
+            }
+            // Shorthand for --param solver=...
+            "--solver" => {
+                if i + 1 < args.len() {
+                    overrides.push(("solver".to_string(), args[i + 1].clone()));
+                    i += 1;
+                } else {
+                    eprintln!("Error: --solver requires a solver (native, microlp or highs)");
+                    return;
+                }
+            }
             _ => {
                 eprintln!("Unknown argument: {}", args[i]);
                 return;
@@ -72,15 +139,53 @@ fn main() {
         }
     };
 
+    let params = match days::Params::new(&day.params(), &overrides) {
+        Ok(params) => params,
+        Err(err) => {
+            eprintln!("Error: {}", err);
+            return;
+        }
+    };
+
+    // An imported file replaces the puzzle input
+    if let Some(path) = import {
+        if let Err(err) = day.import(&params, &path) {
+            eprintln!("Import failed: {}", err);
+        }
+        return;
+    }
+
     let filename = format!("inputs/day{:02}.txt", day_num);
     let input = std::fs::read_to_string(&filename)
         .unwrap_or_else(|_| panic!("Could not read input file {}", filename));
 
+    match day.validate(input.trim(), &params) {
+        Ok(notes) => notes.iter().for_each(|note| println!("{}", note)),
+        Err(report) => {
+            eprintln!("Invalid input in {}:\n{}", filename, report);
+            return;
+        }
+    }
+
+    if let Some(target) = visualize {
+        if let Err(err) = day.visualize(input.trim(), &params, &target) {
+            eprintln!("Visualization failed: {}", err);
+        }
+        return;
+    }
+
+    if let Some(path) = export {
+        if let Err(err) = day.export(input.trim(), &params, &path) {
+            eprintln!("Export failed: {}", err);
+        }
+        return;
+    }
+
     println!("\n❄️🎄🎁 Day {} results are in hohoho 🎁🎄❄️", day_num);
 
     // Warm-up run
-    let _ = day.part1(&input.trim());
-    let _ = day.part2(&input.trim());
+    let _ = day.part1(input.trim(), &params);
+    let _ = day.part2(input.trim(), &params);
 
     // Repeat both parts n times
     let mut total_duration_part1 = 0;
@@ -91,12 +196,12 @@ fn main() {
     for _ in 0..num_runs {
         // Measure Part 1
         let start = Instant::now();
-        part1_result = day.part1(&input.trim());
+        part1_result = day.part1(input.trim(), &params);
         total_duration_part1 += start.elapsed().as_micros();
 
         // Measure Part 2
         let start = Instant::now();
-        part2_result = day.part2(&input.trim());
+        part2_result = day.part2(input.trim(), &params);
         total_duration_part2 += start.elapsed().as_micros();
     }
 
@@ -0,0 +1,358 @@
+use std::collections::HashMap;
+use std::str::FromStr;
+
+// Life-like cellular automaton engine. Neighbour counts are kept incrementally the same way
+// Day04::update_neighbors did it, so a step only touches the neighbourhoods of cells that changed.
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Boundary {
+    // Cells outside the grid are always dead
+    Bounded,
+    // The grid wraps around on both axes
+    Toroidal,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UpdateMode {
+    // Every cell looks at the previous generation
+    Synchronous,
+    // Cells are updated in place in row-major order and see the changes of earlier cells
+    Asynchronous,
+}
+
+impl FromStr for Boundary {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "bounded" => Ok(Boundary::Bounded),
+            "toroidal" => Ok(Boundary::Toroidal),
+            _ => Err(format!("Unknown boundary '{}', expected bounded or toroidal", s)),
+        }
+    }
+}
+
+impl FromStr for UpdateMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "sync" => Ok(UpdateMode::Synchronous),
+            "async" => Ok(UpdateMode::Asynchronous),
+            _ => Err(format!("Unknown update mode '{}', expected sync or async", s)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    // Nothing changed anymore after this many generations
+    StillLife { generation: usize },
+    // The state at `start` came back after `period` generations
+    Cycle { start: usize, period: usize },
+    // Hit the generation limit without settling
+    Running { generation: usize },
+}
+
+pub enum Rule {
+    LifeLike { birth: [bool; 9], survival: [bool; 9] },
+    Custom(Box<dyn Fn(bool, u8) -> bool>),
+}
+
+impl Rule {
+    // Parses B/S notation like "B3/S23" (Conway) or "B/S45678" (Day 4)
+    pub fn parse(notation: &str) -> Result<Self, String> {
+        let mut birth = [false; 9];
+        let mut survival = [false; 9];
+
+        for part in notation.trim().split('/') {
+            let (target, digits) = match part.chars().next() {
+                Some('B') | Some('b') => (&mut birth, &part[1..]),
+                Some('S') | Some('s') => (&mut survival, &part[1..]),
+                _ => return Err(format!("Invalid rule part '{}' in '{}', expected B.../S...", part, notation)),
+            };
+
+            for c in digits.chars() {
+                match c.to_digit(10) {
+                    Some(d) if d <= 8 => target[d as usize] = true,
+                    _ => return Err(format!("Invalid neighbour count '{}' in rule '{}'", c, notation)),
+                }
+            }
+        }
+
+        Ok(Rule::LifeLike { birth, survival })
+    }
+
+    pub fn custom(f: impl Fn(bool, u8) -> bool + 'static) -> Self {
+        Rule::Custom(Box::new(f))
+    }
+
+    pub fn next_state(&self, alive: bool, neighbours: u8) -> bool {
+        match self {
+            Rule::LifeLike { birth, survival } => {
+                let table = if alive { survival } else { birth };
+                table.get(neighbours as usize).copied().unwrap_or(false)
+            }
+            Rule::Custom(f) => f(alive, neighbours),
+        }
+    }
+}
+
+pub struct Automaton {
+    width: usize,
+    height: usize,
+    cells: Vec<u8>,
+    counts: Vec<u8>,
+    // (di, dj, weight) for every non zero kernel entry
+    offsets: Vec<(isize, isize, u8)>,
+    rule: Rule,
+    boundary: Boundary,
+    update: UpdateMode,
+    generation: usize,
+}
+
+impl Automaton {
+    pub fn new(grid: &[Vec<u8>], rule: Rule) -> Self {
+        let height = grid.len();
+        let width = grid.first().map_or(0, |row| row.len());
+        let cells = grid.iter().flat_map(|row| row.iter().map(|&c| (c != 0) as u8)).collect();
+
+        let mut automaton = Self {
+            width,
+            height,
+            cells,
+            counts: vec![0; width * height],
+            offsets: vec![],
+            rule,
+            boundary: Boundary::Bounded,
+            update: UpdateMode::Synchronous,
+            generation: 0,
+        };
+        automaton.set_kernel(&[
+            vec![1, 1, 1],
+            vec![1, 0, 1],
+            vec![1, 1, 1],
+        ]);
+        automaton
+    }
+
+    pub fn with_boundary(mut self, boundary: Boundary) -> Self {
+        self.boundary = boundary;
+        self.recount();
+        self
+    }
+
+    pub fn with_update(mut self, update: UpdateMode) -> Self {
+        self.update = update;
+        self
+    }
+
+    fn set_kernel(&mut self, kernel: &[Vec<u8>]) {
+        let pad = (kernel.len() / 2) as isize;
+        self.offsets = kernel.iter().enumerate().flat_map(|(ki, row)| {
+            row.iter().enumerate()
+                .filter(|&(_, &w)| w != 0)
+                .map(move |(kj, &w)| (ki as isize - pad, kj as isize - pad, w))
+        }).collect();
+        self.recount();
+    }
+
+    pub fn generation(&self) -> usize {
+        self.generation
+    }
+
+    pub fn population(&self) -> usize {
+        self.cells.iter().filter(|&&c| c != 0).count()
+    }
+
+    pub fn grid(&self) -> Vec<Vec<u8>> {
+        self.cells.chunks(self.width.max(1)).map(|row| row.to_vec()).collect()
+    }
+
+    // Maps a shifted position back into the grid, or None if it fell off a bounded grid
+    fn wrap(&self, i: isize, j: isize) -> Option<usize> {
+        let (h, w) = (self.height as isize, self.width as isize);
+        match self.boundary {
+            Boundary::Bounded => {
+                if i >= 0 && j >= 0 && i < h && j < w {
+                    Some(i as usize * self.width + j as usize)
+                } else {
+                    None
+                }
+            }
+            Boundary::Toroidal => Some(i.rem_euclid(h) as usize * self.width + j.rem_euclid(w) as usize),
+        }
+    }
+
+    fn recount(&mut self) {
+        self.counts = vec![0; self.width * self.height];
+        for idx in 0..self.cells.len() {
+            if self.cells[idx] != 0 {
+                self.spread(idx, true);
+            }
+        }
+    }
+
+    // A cell at idx counts for every cell that has it inside its kernel, so walk the kernel mirrored
+    fn spread(&mut self, idx: usize, born: bool) {
+        let (i, j) = ((idx / self.width) as isize, (idx % self.width) as isize);
+        for k in 0..self.offsets.len() {
+            let (di, dj, w) = self.offsets[k];
+            if let Some(n) = self.wrap(i - di, j - dj) {
+                if born {
+                    self.counts[n] += w;
+                } else {
+                    self.counts[n] -= w;
+                }
+            }
+        }
+    }
+
+    fn set(&mut self, idx: usize, alive: bool) {
+        self.cells[idx] = alive as u8;
+        self.spread(idx, alive);
+    }
+
+    // Advances one generation and returns how many cells flipped
+    pub fn step(&mut self) -> usize {
+        self.generation += 1;
+
+        match self.update {
+            UpdateMode::Synchronous => {
+                let changed = (0..self.cells.len())
+                    .filter(|&idx| {
+                        let alive = self.cells[idx] != 0;
+                        self.rule.next_state(alive, self.counts[idx]) != alive
+                    })
+                    .collect::<Vec<usize>>();
+
+                for &idx in &changed {
+                    let alive = self.cells[idx] != 0;
+                    self.set(idx, !alive);
+                }
+                changed.len()
+            }
+            UpdateMode::Asynchronous => {
+                let mut changed = 0;
+                for idx in 0..self.cells.len() {
+                    let alive = self.cells[idx] != 0;
+                    if self.rule.next_state(alive, self.counts[idx]) != alive {
+                        self.set(idx, !alive);
+                        changed += 1;
+                    }
+                }
+                changed
+            }
+        }
+    }
+
+    // Steps until the grid stops changing, repeats an earlier state or max_generations is hit.
+    // on_step is called after every generation with the automaton and the number of flipped cells.
+    pub fn run_with(&mut self, max_generations: usize, mut on_step: impl FnMut(&Automaton, usize)) -> Outcome {
+        let mut seen: HashMap<Vec<u8>, usize> = HashMap::new();
+        seen.insert(self.cells.clone(), self.generation);
+
+        for _ in 0..max_generations {
+            let changed = self.step();
+            on_step(self, changed);
+
+            if changed == 0 {
+                return Outcome::StillLife { generation: self.generation - 1 };
+            }
+            if let Some(&start) = seen.get(&self.cells) {
+                return Outcome::Cycle { start, period: self.generation - start };
+            }
+            seen.insert(self.cells.clone(), self.generation);
+        }
+
+        Outcome::Running { generation: self.generation }
+    }
+
+    pub fn run(&mut self, max_generations: usize) -> Outcome {
+        self.run_with(max_generations, |_, _| {})
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn grid(rows: &[&str]) -> Vec<Vec<u8>> {
+        rows.iter().map(|row| row.chars().map(|c| (c == '@') as u8).collect()).collect()
+    }
+
+    fn conway(rows: &[&str]) -> Automaton {
+        Automaton::new(&grid(rows), Rule::parse("B3/S23").unwrap())
+    }
+
+    #[test]
+    fn rule_notation_errors() {
+        assert!(Rule::parse("B3/S23").is_ok());
+        assert!(Rule::parse("X3/S23").is_err());
+        assert!(Rule::parse("B9/S23").is_err());
+        assert!(Rule::parse("B3/S2a").is_err());
+    }
+
+    #[test]
+    fn block_is_a_still_life() {
+        let mut block = conway(&["....", ".@@.", ".@@.", "...."]);
+        assert_eq!(block.run(10), Outcome::StillLife { generation: 0 });
+        assert_eq!(block.population(), 4);
+    }
+
+    #[test]
+    fn blinker_cycles_with_period_two() {
+        let mut blinker = conway(&[".....", "..@..", "..@..", "..@..", "....."]);
+        assert_eq!(blinker.run(10), Outcome::Cycle { start: 0, period: 2 });
+    }
+
+    #[test]
+    fn toroidal_grid_wraps_around() {
+        // A blinker across the left and right edge only survives if the edges meet
+        let rows = [".....", "@@..@", ".....", ".....", "....."];
+
+        let mut bounded = conway(&rows);
+        assert_eq!(bounded.run(10), Outcome::StillLife { generation: 1 });
+        assert_eq!(bounded.population(), 0);
+
+        let mut toroidal = conway(&rows).with_boundary(Boundary::Toroidal);
+        assert_eq!(toroidal.run(10), Outcome::Cycle { start: 0, period: 2 });
+    }
+
+    #[test]
+    fn asynchronous_update_sees_earlier_cells() {
+        let rows = [".....", "..@..", "..@..", "..@..", "....."];
+
+        // Synchronously the blinker turns, in place every cell of it dies before the births are looked at
+        let mut sync = conway(&rows);
+        assert_eq!(sync.step(), 4);
+        assert_eq!(sync.grid(), grid(&[".....", ".....", ".@@@.", ".....", "....."]));
+
+        let mut async_update = conway(&rows).with_update(UpdateMode::Asynchronous);
+        assert_eq!(async_update.step(), 3);
+        assert_eq!(async_update.population(), 0);
+    }
+
+    #[test]
+    fn day4_example_removes_43_rolls() {
+        let example = grid(&[
+            "..@@.@@@@.",
+            "@@@.@.@.@@",
+            "@@@@@.@.@@",
+            "@.@@@@..@.",
+            "@@.@@@@.@@",
+            ".@@@@@@@.@",
+            ".@.@.@.@@@",
+            "@.@@@.@@@@",
+            ".@@@@@@@@.",
+            "@.@.@@@.@.",
+        ]);
+
+        for rule in [Rule::parse("B/S45678").unwrap(), Rule::custom(|alive, neighbours| alive && neighbours >= 4)] {
+            let mut automaton = Automaton::new(&example, rule);
+            let initial = automaton.population();
+            assert!(matches!(automaton.run(usize::MAX), Outcome::StillLife { .. }));
+            assert_eq!(initial - automaton.population(), 43);
+        }
+    }
+}
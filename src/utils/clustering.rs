@@ -0,0 +1,231 @@
+use std::collections::BinaryHeap;
+
+use super::kd_tree::KdTree;
+use super::union_find::UnionFind;
+
+// Single-linkage clustering is Kruskal's algorithm on the complete graph of the points:
+// take the pairs from closest to farthest and merge whatever they connect.
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Edge {
+    pub a: usize,
+    pub b: usize,
+    pub dist: i64,
+}
+
+// One step of the dendrogram. Clusters 0..n are the points themselves,
+// the cluster created by merge i gets the id n + i and left is the smaller of the two ids
+// (same layout as scipy's linkage matrix).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Merge {
+    pub left: usize,
+    pub right: usize,
+    pub dist: i64,
+    pub size: usize,
+}
+
+// State of the clustering right after a merge, for plotting how the points percolate
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PercolationStep {
+    pub edge: Edge,
+    pub components: usize,
+    pub largest: usize,
+}
+
+// Hands out all pairs ordered by (dist, a, b)
+type PairSource<'a> = Box<dyn Fn() -> Box<dyn Iterator<Item = Edge> + 'a> + 'a>;
+
+pub struct SingleLinkage<'a> {
+    n: usize,
+    dist: Box<dyn Fn(usize, usize) -> i64 + 'a>,
+    // Spatial index that can hand out the pairs in order, without it every pair gets looked at
+    index: Option<PairSource<'a>>,
+}
+
+impl<'a> SingleLinkage<'a> {
+    // Distances are computed on demand, there is never a list of all n² pairs in memory
+    pub fn new<P>(points: &'a [P], metric: impl Fn(&P, &P) -> i64 + 'a) -> Self {
+        Self {
+            n: points.len(),
+            dist: Box::new(move |a, b| metric(&points[a], &points[b])),
+            index: None,
+        }
+    }
+
+    // Clustering under the tree's metric backed by the k-d tree, for point sets too large for all pairs.
+    // Only closest_pairs uses the tree, the spanning tree is cheaper from the dense graph (see mst).
+    pub fn indexed<const D: usize>(tree: &'a KdTree<D>) -> Self {
+        Self {
+            n: tree.len(),
+            dist: Box::new(move |a, b| tree.distance(a, b)),
+            index: Some(Box::new(move || Box::new(tree.pairs()))),
+        }
+    }
+
+    // The k closest pairs ordered by (dist, a, b), so ties always resolve the same way.
+    // Keeps a max heap of the best k seen so far instead of sorting every pair.
+    pub fn closest_pairs(&self, k: usize) -> Vec<Edge> {
+        if let Some(index) = &self.index {
+            return index().take(k).collect();
+        }
+
+        let mut heap = BinaryHeap::with_capacity(k + 1);
+
+        for a in 0..self.n {
+            for b in (a + 1)..self.n {
+                let key = ((self.dist)(a, b), a, b);
+                if heap.len() < k {
+                    heap.push(key);
+                } else if heap.peek().is_some_and(|&worst| key < worst) {
+                    heap.pop();
+                    heap.push(key);
+                }
+            }
+        }
+
+        heap.into_sorted_vec().into_iter().map(|(dist, a, b)| Edge { a, b, dist }).collect()
+    }
+
+    // The clustering after the `connections` closest pairs have been joined,
+    // pairs that were already in the same cluster still count as a connection
+    pub fn connect(&self, connections: usize) -> UnionFind {
+        let mut uf = UnionFind::new(self.n);
+        for edge in self.closest_pairs(connections) {
+            uf.union(edge.a, edge.b);
+        }
+        uf
+    }
+
+    // Sizes of the `count` largest clusters after `connections` pairs have been joined
+    pub fn largest_clusters(&self, connections: usize, count: usize) -> Vec<usize> {
+        self.connect(connections).component_sizes().into_iter().take(count).collect()
+    }
+
+    // The clustering after exactly `merges` pairs that actually joined two clusters.
+    // No day asks for it, part 1 counts connections including the ones that merge nothing.
+    #[allow(dead_code)]
+    pub fn after_merges(&self, merges: usize) -> UnionFind {
+        let mut uf = UnionFind::new(self.n);
+        for edge in self.mst().iter().take(merges) {
+            uf.union(edge.a, edge.b);
+        }
+        uf
+    }
+
+    // Edges of the minimum spanning tree in the order Kruskal would merge them.
+    // Prim's algorithm on the dense graph is O(n²) without any edge list, and since edges are
+    // compared by (dist, a, b) there are no ties, so it finds exactly the tree Kruskal would.
+    // Kruskal over the index's pair stream would have to walk every pair up to the longest tree
+    // edge, which for a single far outlier is nearly all of them, so the index isn't used here.
+    pub fn mst(&self) -> Vec<Edge> {
+        if self.n == 0 {
+            return vec![];
+        }
+
+        let mut in_tree = vec![false; self.n];
+        // Cheapest known (dist, a, b) connecting each point to the tree
+        let mut best = vec![(i64::MAX, usize::MAX, usize::MAX); self.n];
+        let mut tree = Vec::with_capacity(self.n - 1);
+
+        let mut current = 0;
+        in_tree[0] = true;
+
+        for _ in 1..self.n {
+            let mut next = usize::MAX;
+            for v in 0..self.n {
+                if in_tree[v] {
+                    continue;
+                }
+                let key = ((self.dist)(current, v), current.min(v), current.max(v));
+                if key < best[v] {
+                    best[v] = key;
+                }
+                if next == usize::MAX || best[v] < best[next] {
+                    next = v;
+                }
+            }
+
+            let (dist, a, b) = best[next];
+            tree.push(Edge { a, b, dist });
+            in_tree[next] = true;
+            current = next;
+        }
+
+        tree.sort_unstable_by_key(|e| (e.dist, e.a, e.b));
+        tree
+    }
+
+    // The pair whose merge makes everything one single cluster
+    pub fn connecting_edge(&self) -> Option<Edge> {
+        if self.n < 2 {
+            return None;
+        }
+        self.mst().last().copied()
+    }
+
+    // Number of clusters and size of the largest one after every merge
+    pub fn percolation(&self) -> Vec<PercolationStep> {
+        let mut uf = UnionFind::new(self.n);
+        let mut largest = if self.n > 0 { 1 } else { 0 };
+
+        self.mst().into_iter().map(|edge| {
+            uf.union(edge.a, edge.b);
+            largest = largest.max(uf.size(edge.a));
+            PercolationStep { edge, components: uf.components(), largest }
+        }).collect()
+    }
+
+    pub fn dendrogram(&self) -> Vec<Merge> {
+        let mut uf = UnionFind::new(self.n);
+        // Current dendrogram id of the cluster each union find root stands for
+        let mut cluster_id = (0..self.n).collect::<Vec<usize>>();
+        let mut merges = Vec::with_capacity(self.n.saturating_sub(1));
+
+        for edge in self.mst() {
+            let (a, b) = (cluster_id[uf.find(edge.a)], cluster_id[uf.find(edge.b)]);
+            let (left, right) = (a.min(b), a.max(b));
+            uf.union(edge.a, edge.b);
+
+            let root = uf.find(edge.a);
+            cluster_id[root] = self.n + merges.len();
+            merges.push(Merge { left, right, dist: edge.dist, size: uf.size(root) });
+        }
+        merges
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dendrogram_numbers_clusters_like_scipy() {
+        let points = [0i64, 1, 10, 12];
+        let clustering = SingleLinkage::new(&points, |a, b| (a - b).abs());
+
+        assert_eq!(clustering.dendrogram(), vec![
+            Merge { left: 0, right: 1, dist: 1, size: 2 },
+            Merge { left: 2, right: 3, dist: 2, size: 2 },
+            Merge { left: 4, right: 5, dist: 9, size: 4 },
+        ]);
+
+        // The smaller id always comes first, no matter which end of the edge it was found from
+        let points = [10i64, 12, 0, 1];
+        let clustering = SingleLinkage::new(&points, |a, b| (a - b).abs());
+        assert_eq!(clustering.dendrogram(), vec![
+            Merge { left: 2, right: 3, dist: 1, size: 2 },
+            Merge { left: 0, right: 1, dist: 2, size: 2 },
+            Merge { left: 4, right: 5, dist: 9, size: 4 },
+        ]);
+    }
+
+    #[test]
+    fn after_merges_skips_pairs_that_merge_nothing() {
+        // The three closest pairs are all inside {0, 1, 2}, only two of them merge anything
+        let points = [0i64, 1, 2, 10, 13];
+        let clustering = SingleLinkage::new(&points, |a, b| (a - b).abs());
+
+        assert_eq!(clustering.connect(3).component_sizes(), vec![3, 1, 1]);
+        assert_eq!(clustering.after_merges(3).component_sizes(), vec![3, 2]);
+    }
+}
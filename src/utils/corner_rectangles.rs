@@ -0,0 +1,147 @@
+use super::geometry::Point2;
+
+// Largest axis aligned rectangle whose opposite corners are two of the given points, counting
+// the tiles on its border too, so a and b span (|dx| + 1) * (|dy| + 1).
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CornerRect {
+    pub a: Point2,
+    pub b: Point2,
+    pub area: i64,
+}
+
+impl CornerRect {
+    pub fn new(a: Point2, b: Point2) -> Self {
+        Self { a, b, area: tile_area(&a, &b) }
+    }
+}
+
+pub fn tile_area(a: &Point2, b: &Point2) -> i64 {
+    ((a.x() - b.x()).abs() + 1) * ((a.y() - b.y()).abs() + 1)
+}
+
+// Every pair, O(n²)
+pub fn largest_brute_force(points: &[Point2]) -> Option<CornerRect> {
+    let mut best: Option<CornerRect> = None;
+    for i in 0..points.len() {
+        for j in i + 1..points.len() {
+            let area = tile_area(&points[i], &points[j]);
+            if best.is_none_or(|b| area > b.area) {
+                best = Some(CornerRect::new(points[i], points[j]));
+            }
+        }
+    }
+    best
+}
+
+// O(n log n). The best rectangle from lower left to upper right uses a point nobody else lies
+// below and left of, and one nobody lies above and right of, so only the two staircases matter.
+// Walking the lower staircase left to right, the best partner on the upper staircase never moves
+// left, and divide and conquer over that monotone matrix needs O(n log n) area evaluations.
+// The other diagonal is the same problem with y mirrored.
+pub fn largest_staircase(points: &[Point2]) -> Option<CornerRect> {
+    if points.len() < 2 {
+        return None;
+    }
+
+    let mirrored = points.iter().map(|p| Point2::new(p.x(), -p.y())).collect::<Vec<Point2>>();
+    let rising = largest_rising(points);
+    let falling = largest_rising(&mirrored).map(|r| CornerRect {
+        a: Point2::new(r.a.x(), -r.a.y()),
+        b: Point2::new(r.b.x(), -r.b.y()),
+        area: r.area,
+    });
+
+    match (rising, falling) {
+        (Some(r), Some(f)) => Some(if f.area > r.area { f } else { r }),
+        (r, f) => r.or(f),
+    }
+}
+
+// Best pair where b lies above and right of a (or level with it)
+fn largest_rising(points: &[Point2]) -> Option<CornerRect> {
+    let mut sorted = points.to_vec();
+    sorted.sort_unstable();
+
+    // Lower staircase: x ascending, y strictly falling
+    let mut lower: Vec<Point2> = vec![];
+    for p in &sorted {
+        if lower.last().is_none_or(|last| p.y() < last.y()) {
+            lower.push(*p);
+        }
+    }
+
+    // Upper staircase, built from the right: x ascending afterwards, y strictly falling too
+    let mut upper: Vec<Point2> = vec![];
+    for p in sorted.iter().rev() {
+        if upper.last().is_none_or(|last| p.y() > last.y()) {
+            upper.push(*p);
+        }
+    }
+    upper.reverse();
+
+    let mut best = None;
+    best_partners(&lower, &upper, 0, lower.len(), 0, upper.len() - 1, &mut best);
+    best
+}
+
+// Signed version of the tile area, pairs in the wrong order come out <= 0 and never win.
+// A point on both staircases may pair with itself, but that 1 only wins if all points coincide.
+fn rising_area(a: &Point2, b: &Point2) -> i64 {
+    (b.x() - a.x() + 1) * (b.y() - a.y() + 1)
+}
+
+// Finds the best partner in upper[opt_lo..=opt_hi] for every lower[lo..hi]
+fn best_partners(
+    lower: &[Point2],
+    upper: &[Point2],
+    lo: usize,
+    hi: usize,
+    opt_lo: usize,
+    opt_hi: usize,
+    best: &mut Option<CornerRect>,
+) {
+    if lo >= hi {
+        return;
+    }
+
+    let mid = (lo + hi) / 2;
+    let a = lower[mid];
+    let (mut opt, mut opt_area) = (opt_lo, i64::MIN);
+    for (j, b) in upper.iter().enumerate().take(opt_hi + 1).skip(opt_lo) {
+        let area = rising_area(&a, b);
+        if area > opt_area {
+            (opt, opt_area) = (j, area);
+        }
+    }
+
+    if best.is_none_or(|b: CornerRect| opt_area > b.area) {
+        *best = Some(CornerRect { a, b: upper[opt], area: opt_area });
+    }
+
+    best_partners(lower, upper, lo, mid, opt_lo, opt, best);
+    best_partners(lower, upper, mid + 1, hi, opt, opt_hi, best);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::test_points::random_points;
+
+    #[test]
+    fn staircase_matches_brute_force() {
+        for seed in 0..500u64 {
+            let n = 1 + (seed % 60) as usize;
+            let range = [3, 10, 1000, 1_000_000][(seed % 4) as usize];
+            let points = random_points::<2>(seed, n, range);
+
+            let brute = largest_brute_force(&points).map(|r| r.area);
+            let fast = largest_staircase(&points);
+            assert_eq!(brute, fast.map(|r| r.area), "seed {}: {:?}", seed, points);
+            if let Some(r) = fast {
+                assert_eq!(tile_area(&r.a, &r.b), r.area);
+                assert!(points.contains(&r.a) && points.contains(&r.b));
+            }
+        }
+    }
+}
@@ -0,0 +1,192 @@
+use std::fmt;
+use std::ops::{Add, AddAssign, Index, IndexMut, Mul, Neg, Sub, SubAssign};
+use std::str::FromStr;
+
+// Integer point / vector in N dimensions, used for the 2D and 3D days alike
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Point<const N: usize> {
+    pub coords: [i64; N],
+}
+
+pub type Point2 = Point<2>;
+
+impl<const N: usize> Point<N> {
+    pub fn x(&self) -> i64 {
+        self.coords[0]
+    }
+
+    pub fn y(&self) -> i64 {
+        self.coords[1]
+    }
+
+    pub fn dot(&self, other: &Self) -> i64 {
+        self.coords.iter().zip(&other.coords).map(|(a, b)| a * b).sum()
+    }
+
+    // Parses one point per line like "162,817,812", errors name the offending line
+    pub fn parse_lines(input: &str) -> Result<Vec<Self>, String> {
+        input.lines().enumerate()
+            .filter(|(_, line)| !line.trim().is_empty())
+            .map(|(idx, line)| line.parse::<Self>().map_err(|err| format!("Line {}: {}", idx + 1, err)))
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Norm {
+    #[default]
+    SquaredEuclidean,
+    Manhattan,
+    Chebyshev,
+}
+
+// Distance function picked at runtime, like "manhattan" or "euclidean:1,1,2".
+// The optional weights scale the per axis differences, missing weights count as 1.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Metric {
+    pub norm: Norm,
+    pub weights: Vec<i64>,
+}
+
+impl Metric {
+    pub fn distance<const N: usize>(&self, a: &Point<N>, b: &Point<N>) -> i64 {
+        self.combine(a.coords.iter().zip(&b.coords).map(|(x, y)| (x - y).abs()))
+    }
+
+    // Distance for the given absolute per axis differences. The result only grows with each gap,
+    // so feeding it the gaps to a bounding box gives a lower bound for everything inside.
+    pub fn combine(&self, gaps: impl Iterator<Item = i64>) -> i64 {
+        let weighted = gaps.enumerate().map(|(axis, gap)| gap * self.weights.get(axis).copied().unwrap_or(1));
+        match self.norm {
+            Norm::SquaredEuclidean => weighted.map(|d| d * d).sum(),
+            Norm::Manhattan => weighted.sum(),
+            Norm::Chebyshev => weighted.max().unwrap_or(0),
+        }
+    }
+}
+
+impl FromStr for Metric {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, String> {
+        let (name, weights) = match s.trim().split_once(':') {
+            Some((name, weights)) => (name, Some(weights)),
+            None => (s.trim(), None),
+        };
+
+        let norm = match name.to_lowercase().as_str() {
+            "euclidean" | "squared_euclidean" | "l2" => Norm::SquaredEuclidean,
+            "manhattan" | "taxicab" | "l1" => Norm::Manhattan,
+            "chebyshev" | "max" | "linf" => Norm::Chebyshev,
+            _ => return Err(format!("Unknown metric '{}', expected euclidean, manhattan or chebyshev", name)),
+        };
+
+        let weights = match weights {
+            Some(weights) => weights.split(',').map(|w| match w.trim().parse::<i64>() {
+                Ok(w) if w >= 0 => Ok(w),
+                _ => Err(format!("Invalid axis weight '{}' in metric '{}'", w.trim(), s.trim())),
+            }).collect::<Result<Vec<i64>, String>>()?,
+            None => vec![],
+        };
+
+        Ok(Metric { norm, weights })
+    }
+}
+
+impl Point<2> {
+    pub fn new(x: i64, y: i64) -> Self {
+        Self { coords: [x, y] }
+    }
+}
+
+impl<const N: usize> FromStr for Point<N> {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, String> {
+        let parts = s.trim().split(',').collect::<Vec<&str>>();
+        if parts.len() != N {
+            return Err(format!("expected {} comma separated coordinates but got {} in '{}'", N, parts.len(), s.trim()));
+        }
+
+        let mut coords = [0; N];
+        for (coord, part) in coords.iter_mut().zip(parts) {
+            *coord = part.trim().parse::<i64>()
+                .map_err(|_| format!("invalid coordinate '{}' in '{}'", part.trim(), s.trim()))?;
+        }
+        Ok(Self { coords })
+    }
+}
+
+impl<const N: usize> fmt::Display for Point<N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let parts = self.coords.iter().map(|c| c.to_string()).collect::<Vec<String>>();
+        write!(f, "{}", parts.join(","))
+    }
+}
+
+impl<const N: usize> Index<usize> for Point<N> {
+    type Output = i64;
+
+    fn index(&self, axis: usize) -> &i64 {
+        &self.coords[axis]
+    }
+}
+
+impl<const N: usize> IndexMut<usize> for Point<N> {
+    fn index_mut(&mut self, axis: usize) -> &mut i64 {
+        &mut self.coords[axis]
+    }
+}
+
+impl<const N: usize> Add for Point<N> {
+    type Output = Self;
+
+    fn add(mut self, other: Self) -> Self {
+        self += other;
+        self
+    }
+}
+
+impl<const N: usize> AddAssign for Point<N> {
+    fn add_assign(&mut self, other: Self) {
+        for (a, b) in self.coords.iter_mut().zip(other.coords) {
+            *a += b;
+        }
+    }
+}
+
+impl<const N: usize> Sub for Point<N> {
+    type Output = Self;
+
+    fn sub(mut self, other: Self) -> Self {
+        self -= other;
+        self
+    }
+}
+
+impl<const N: usize> SubAssign for Point<N> {
+    fn sub_assign(&mut self, other: Self) {
+        for (a, b) in self.coords.iter_mut().zip(other.coords) {
+            *a -= b;
+        }
+    }
+}
+
+impl<const N: usize> Mul<i64> for Point<N> {
+    type Output = Self;
+
+    fn mul(mut self, factor: i64) -> Self {
+        for a in self.coords.iter_mut() {
+            *a *= factor;
+        }
+        self
+    }
+}
+
+impl<const N: usize> Neg for Point<N> {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        self * -1
+    }
+}
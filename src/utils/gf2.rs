@@ -0,0 +1,250 @@
+// Linear algebra over GF(2): bits are the numbers, XOR is addition and AND multiplication.
+// Vectors and matrix rows are packed into u64 words, so eliminating a row is a handful of XORs.
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct BitVec {
+    words: Vec<u64>,
+    len: usize,
+}
+
+impl BitVec {
+    pub fn zeros(len: usize) -> Self {
+        Self { words: vec![0; len.div_ceil(64)], len }
+    }
+
+    pub fn from_bits(bits: impl IntoIterator<Item = bool>) -> Self {
+        let bits = bits.into_iter().collect::<Vec<bool>>();
+        let mut v = Self::zeros(bits.len());
+        for (i, &bit) in bits.iter().enumerate() {
+            v.set(i, bit);
+        }
+        v
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn get(&self, i: usize) -> bool {
+        self.words[i / 64] >> (i % 64) & 1 == 1
+    }
+
+    pub fn set(&mut self, i: usize, bit: bool) {
+        if bit {
+            self.words[i / 64] |= 1 << (i % 64);
+        } else {
+            self.words[i / 64] &= !(1 << (i % 64));
+        }
+    }
+
+    pub fn xor_assign(&mut self, other: &BitVec) {
+        for (a, b) in self.words.iter_mut().zip(&other.words) {
+            *a ^= b;
+        }
+    }
+
+    pub fn count_ones(&self) -> usize {
+        self.words.iter().map(|w| w.count_ones() as usize).sum()
+    }
+
+    pub fn ones(&self) -> impl Iterator<Item = usize> + '_ {
+        (0..self.len).filter(|&i| self.get(i))
+    }
+}
+
+// 2^24 candidates still take well under a second
+const MAX_FREE: usize = 24;
+
+#[derive(Debug, Clone)]
+pub struct BitMatrix {
+    rows: Vec<BitVec>,
+    cols: usize,
+}
+
+// All x with A x = b: the particular solution plus any XOR combination of the null space basis
+#[derive(Debug, Clone)]
+pub struct SolutionSpace {
+    pub particular: BitVec,
+    pub null_space: Vec<BitVec>,
+}
+
+impl BitMatrix {
+    pub fn zeros(rows: usize, cols: usize) -> Self {
+        Self { rows: vec![BitVec::zeros(cols); rows], cols }
+    }
+
+    pub fn rows(&self) -> usize {
+        self.rows.len()
+    }
+
+    pub fn set(&mut self, r: usize, c: usize, bit: bool) {
+        self.rows[r].set(c, bit);
+    }
+
+    // Gaussian elimination to reduced row echelon form on a copy, None if the system is inconsistent
+    pub fn solve(&self, rhs: &BitVec) -> Option<SolutionSpace> {
+        assert_eq!(rhs.len(), self.rows(), "Right hand side doesn't match the number of rows");
+
+        // The right hand side rides along as the extra column `cols`
+        let mut rows = self.rows.iter().enumerate().map(|(r, row)| {
+            let mut augmented = BitVec::zeros(self.cols + 1);
+            for c in row.ones() {
+                augmented.set(c, true);
+            }
+            augmented.set(self.cols, rhs.get(r));
+            augmented
+        }).collect::<Vec<BitVec>>();
+
+        let mut pivots = vec![];
+        let mut rank = 0;
+        for c in 0..self.cols {
+            let Some(p) = (rank..rows.len()).find(|&r| rows[r].get(c)) else {
+                continue;
+            };
+            rows.swap(rank, p);
+            let pivot = rows[rank].clone();
+            for (r, row) in rows.iter_mut().enumerate() {
+                if r != rank && row.get(c) {
+                    row.xor_assign(&pivot);
+                }
+            }
+            pivots.push(c);
+            rank += 1;
+        }
+
+        // A zero row left with a 1 on the right says 0 = 1
+        if rows[rank..].iter().any(|row| row.get(self.cols)) {
+            return None;
+        }
+
+        let mut particular = BitVec::zeros(self.cols);
+        for (r, &c) in pivots.iter().enumerate() {
+            particular.set(c, rows[r].get(self.cols));
+        }
+
+        let free = (0..self.cols).filter(|c| !pivots.contains(c)).collect::<Vec<usize>>();
+        let null_space = free.into_iter().map(|f| {
+            let mut v = BitVec::zeros(self.cols);
+            v.set(f, true);
+            for (r, &c) in pivots.iter().enumerate() {
+                v.set(c, rows[r].get(f));
+            }
+            v
+        }).collect();
+
+        Some(SolutionSpace { particular, null_space })
+    }
+}
+
+impl SolutionSpace {
+    pub fn dimension(&self) -> usize {
+        self.null_space.len()
+    }
+
+    // Solution with the lowest cost, where None rules a solution out. Walks all 2^k choices of the
+    // k free variables in Gray code order, so every step only XORs one basis vector onto the previous candidate.
+    // Refuses more than MAX_FREE free variables instead of running (nearly) forever.
+    pub fn cheapest(&self, cost: impl Fn(&BitVec) -> Option<i64>) -> Result<Option<BitVec>, String> {
+        if self.dimension() > MAX_FREE {
+            return Err(format!(
+                "{} free variables are too many to try every solution, at most {} are supported",
+                self.dimension(), MAX_FREE
+            ));
+        }
+
+        let mut current = self.particular.clone();
+        let mut best = cost(&current).map(|c| (c, current.clone()));
+
+        for step in 1u64..(1 << self.dimension()) {
+            current.xor_assign(&self.null_space[step.trailing_zeros() as usize]);
+            if let Some(c) = cost(&current) && best.as_ref().is_none_or(|(best_cost, _)| c < *best_cost) {
+                best = Some((c, current.clone()));
+            }
+        }
+        Ok(best.map(|(_, v)| v))
+    }
+}
+
+impl FromIterator<BitVec> for BitMatrix {
+    // Rows of equal length
+    fn from_iter<I: IntoIterator<Item = BitVec>>(iter: I) -> Self {
+        let rows = iter.into_iter().collect::<Vec<BitVec>>();
+        let cols = rows.first().map_or(0, |r| r.len());
+        Self { rows, cols }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::test_points::xorshift;
+
+    fn matrix(rows: &[&str]) -> BitMatrix {
+        rows.iter().map(|row| BitVec::from_bits(row.chars().map(|c| c == '1'))).collect()
+    }
+
+    fn bits(s: &str) -> BitVec {
+        BitVec::from_bits(s.chars().map(|c| c == '1'))
+    }
+
+    // A x
+    fn product(a: &BitMatrix, x: &BitVec) -> BitVec {
+        BitVec::from_bits(a.rows.iter().map(|row| row.ones().filter(|&c| x.get(c)).count() % 2 == 1))
+    }
+
+    #[test]
+    fn inconsistent_system_has_no_solution() {
+        // The third row is the sum of the first two, but its right hand side isn't
+        let a = matrix(&["110", "011", "101"]);
+        assert!(a.solve(&bits("100")).is_none());
+        assert!(a.solve(&bits("101")).is_some());
+    }
+
+    #[test]
+    fn null_space_dimension_is_columns_minus_rank() {
+        let a = matrix(&["1100", "0110", "1010"]);
+        let space = a.solve(&bits("110")).unwrap();
+        assert_eq!(space.dimension(), 2);
+
+        // Every combination of the basis solves the system
+        for mask in 0..4 {
+            let mut x = space.particular.clone();
+            for (i, v) in space.null_space.iter().enumerate() {
+                if mask >> i & 1 == 1 {
+                    x.xor_assign(v);
+                }
+            }
+            assert_eq!(product(&a, &x), bits("110"));
+        }
+    }
+
+    #[test]
+    fn cheapest_matches_subset_search() {
+        let mut next = xorshift(1);
+
+        for _ in 0..200 {
+            let (rows, cols) = (1 + (next() % 6) as usize, 1 + (next() % 10) as usize);
+            let a = (0..rows).map(|_| BitVec::from_bits((0..cols).map(|_| next() % 2 == 1))).collect::<BitMatrix>();
+            let rhs = BitVec::from_bits((0..rows).map(|_| next() % 2 == 1));
+            let weights = (0..cols).map(|_| (next() % 5) as i64).collect::<Vec<i64>>();
+            let cost = |x: &BitVec| Some(x.ones().map(|c| weights[c]).sum::<i64>());
+
+            let brute = (0..1u32 << cols)
+                .map(|mask| BitVec::from_bits((0..cols).map(|c| mask >> c & 1 == 1)))
+                .filter(|x| product(&a, x) == rhs)
+                .filter_map(|x| cost(&x))
+                .min();
+
+            let fast = a.solve(&rhs).map(|space| space.cheapest(cost).unwrap().unwrap());
+            assert_eq!(fast.as_ref().and_then(cost), brute);
+            assert!(fast.is_none_or(|x| product(&a, &x) == rhs));
+        }
+    }
+
+    #[test]
+    fn too_many_free_variables_are_refused() {
+        let a = BitMatrix::zeros(1, MAX_FREE + 1);
+        let space = a.solve(&BitVec::zeros(1)).unwrap();
+        assert!(space.cheapest(|x| Some(x.count_ones() as i64)).is_err());
+    }
+}
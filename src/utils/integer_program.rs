@@ -0,0 +1,536 @@
+use std::fmt;
+use std::str::FromStr;
+
+use good_lp::{Expression, ResolutionError, Solution, SolverModel, Variable, variable, variables};
+use num::rational::Ratio;
+use num::{Signed, Zero};
+
+type Q = Ratio<i128>;
+
+// Branch and bound gives up below this many nested branches. Without upper bounds a program like
+// 2x - 2z = 1 with x = y can keep splitting forever, and every level costs a few stack frames.
+const MAX_DEPTH: usize = 500;
+
+// Exact solver for: minimise costs·x subject to A x = b and x >= 0 integer. No floats anywhere:
+// the LP relaxation is solved with a simplex over the rationals (Gaussian pivoting on the tableau),
+// and branch and bound splits on fractional variables until the relaxation has an integer optimum.
+// With integer costs the optimum of a relaxation rounded up bounds everything below it.
+// Optionally every variable has an upper bound, and exactly k of them have to be non-zero.
+pub struct IntegerProgram {
+    matrix: Vec<Vec<i64>>,
+    rhs: Vec<i64>,
+    costs: Vec<i64>,
+    upper: Vec<Option<i64>>,
+    distinct: Option<usize>,
+}
+
+// Who does the solving: the exact solver below or one of the good_lp backends
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Backend {
+    Native,
+    Microlp,
+    // Only available when built with --features highs
+    Highs,
+}
+
+impl FromStr for Backend {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "native" => Ok(Backend::Native),
+            "microlp" => Ok(Backend::Microlp),
+            "highs" => Ok(Backend::Highs),
+            _ => Err(format!("Unknown solver '{}', expected native, microlp or highs", s)),
+        }
+    }
+}
+
+impl fmt::Display for Backend {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            Backend::Native => "native",
+            Backend::Microlp => "microlp",
+            Backend::Highs => "highs",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+// How solving a program ended
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Outcome {
+    Optimal(Vec<i64>),
+    // With a reason, e.g. that only fractional values would fit
+    Infeasible(String),
+    // The cost can be made as small as you like
+    Unbounded,
+}
+
+// State of the depth first search: the cheapest integer solution so far and how deep it is
+struct Search {
+    best: Option<(i128, Vec<i64>)>,
+    depth: usize,
+    gave_up: bool,
+}
+
+enum Relaxation {
+    Optimal(Vec<Q>),
+    Infeasible,
+    Unbounded,
+}
+
+impl IntegerProgram {
+    // One row per equation, one column per variable, every variable costs 1
+    pub fn new(matrix: Vec<Vec<i64>>, rhs: Vec<i64>) -> Self {
+        let vars = matrix.first().map_or(0, |row| row.len());
+        Self { matrix, rhs, costs: vec![1; vars], upper: vec![None; vars], distinct: None }
+    }
+
+    pub fn with_costs(mut self, costs: Vec<i64>) -> Self {
+        self.costs = costs;
+        self
+    }
+
+    // None leaves a variable unbounded above
+    pub fn with_upper_bounds(mut self, upper: Vec<Option<i64>>) -> Self {
+        self.upper = upper;
+        self
+    }
+
+    // Exactly k variables non-zero. The good_lp backends need an upper bound on every variable for that.
+    pub fn with_distinct(mut self, k: usize) -> Self {
+        self.distinct = Some(k);
+        self
+    }
+
+    pub fn vars(&self) -> usize {
+        self.costs.len()
+    }
+
+    pub fn matrix(&self) -> &[Vec<i64>] {
+        &self.matrix
+    }
+
+    pub fn rhs(&self) -> &[i64] {
+        &self.rhs
+    }
+
+    pub fn costs(&self) -> &[i64] {
+        &self.costs
+    }
+
+    pub fn upper(&self) -> &[Option<i64>] {
+        &self.upper
+    }
+
+    pub fn distinct(&self) -> Option<usize> {
+        self.distinct
+    }
+
+    // Cheapest non-negative integer x within the upper bounds
+    pub fn solve(&self) -> Outcome {
+        // A row whose coefficients share a factor the right hand side doesn't have can't be met by
+        // integers at all, which branching alone would never find out without upper bounds
+        for (i, (row, &target)) in self.matrix.iter().zip(&self.rhs).enumerate() {
+            let factor = row.iter().fold(0, |g, &a| num::integer::gcd(g, a));
+            if factor > 1 && target % factor != 0 {
+                return Outcome::Infeasible(format!(
+                    "row {} only has fractional solutions, its coefficients are multiples of {} but {} is not",
+                    i + 1, factor, target
+                ));
+            }
+        }
+
+        let (mut lower, mut upper) = (vec![0; self.vars()], self.upper.clone());
+        match self.relax(&lower, &upper) {
+            Relaxation::Infeasible => return Outcome::Infeasible("no non-negative solution, not even a fractional one".to_string()),
+            Relaxation::Unbounded => return Outcome::Unbounded,
+            Relaxation::Optimal(_) => {}
+        }
+
+        let mut search = Search { best: None, depth: 0, gave_up: false };
+        self.branch(&mut lower, &mut upper, &mut search);
+        if search.gave_up {
+            return Outcome::Infeasible(format!("gave up after branching {} levels deep, no optimum is proven", MAX_DEPTH));
+        }
+        match search.best {
+            Some((_, x)) => Outcome::Optimal(x),
+            None => match self.distinct {
+                Some(k) => Outcome::Infeasible(format!("no integer solution has exactly {} non-zero variables", k)),
+                None => Outcome::Infeasible("only fractional solutions exist".to_string()),
+            },
+        }
+    }
+
+    pub fn solve_with(&self, backend: Backend) -> Result<Outcome, String> {
+        match backend {
+            Backend::Native => Ok(self.solve()),
+            Backend::Microlp => self.solve_lp(good_lp::microlp),
+            #[cfg(feature = "highs")]
+            Backend::Highs => self.solve_lp(good_lp::highs),
+            #[cfg(not(feature = "highs"))]
+            Backend::Highs => Err("HiGHS support is not compiled in, build with --features highs".to_string()),
+        }
+    }
+
+    // Same model handed to a good_lp solver. Those work in f64, so the presses are rounded to the
+    // nearest integer instead of truncated (41.9999 is 42, not 41). The distinct limit becomes a 0/1
+    // variable z[j] per x[j] with z[j] <= x[j] <= upper[j] * z[j] and sum z = k.
+    fn solve_lp<S: good_lp::Solver>(&self, solver: S) -> Result<Outcome, String>
+    where
+        S::Model: SolverModel<Error = ResolutionError>,
+    {
+        let mut vars = variables!();
+        let x: Vec<Variable> = self.upper.iter().map(|&upper| match upper {
+            Some(upper) => vars.add(variable().integer().min(0).max(upper as f64)),
+            None => vars.add(variable().integer().min(0)),
+        }).collect();
+        let z: Vec<Variable> = match self.distinct {
+            Some(_) => (0..self.vars()).map(|_| vars.add(variable().binary())).collect(),
+            None => vec![],
+        };
+
+        let objective = x.iter().zip(&self.costs).map(|(&v, &c)| c as f64 * v).sum::<Expression>();
+        let mut problem = vars.minimise(objective).using(solver);
+        for (row, &target) in self.matrix.iter().zip(&self.rhs) {
+            let lhs = x.iter().zip(row).map(|(&v, &a)| a as f64 * v).sum::<Expression>();
+            problem = problem.with(lhs.eq(target as f64));
+        }
+        if let Some(k) = self.distinct {
+            let mut used = Expression::from(0);
+            for (&v, (&z, &upper)) in x.iter().zip(z.iter().zip(&self.upper)) {
+                let upper = upper.ok_or("Limiting the number of distinct variables needs an upper bound on every variable")?;
+                problem = problem.with((v - upper as f64 * z).leq(0)).with((z - v).leq(0));
+                used += z;
+            }
+            problem = problem.with(used.eq(k as f64));
+        }
+
+        match problem.solve() {
+            Ok(solution) => Ok(Outcome::Optimal(x.iter().map(|&v| solution.value(v).round() as i64).collect())),
+            Err(ResolutionError::Infeasible) => Ok(Outcome::Infeasible("the solver found no integer solution".to_string())),
+            Err(ResolutionError::Unbounded) => Ok(Outcome::Unbounded),
+            Err(err) => Err(err.to_string()),
+        }
+    }
+
+    // LP relaxation with lower[j] <= x[j] <= upper[j]. Lower bounds are shifted away
+    // (x = lower + y), upper bounds become extra rows y + slack = upper - lower.
+    fn relax(&self, lower: &[i64], upper: &[Option<i64>]) -> Relaxation {
+        let n = self.vars();
+        let bounded = (0..n).filter(|&j| upper[j].is_some()).collect::<Vec<usize>>();
+        let width = n + bounded.len();
+
+        let mut a = vec![];
+        let mut b = vec![];
+        for (row, &target) in self.matrix.iter().zip(&self.rhs) {
+            let mut coeffs = row.iter().map(|&v| Q::from_integer(v as i128)).collect::<Vec<Q>>();
+            coeffs.resize(width, Q::zero());
+            let shifted = target as i128 - row.iter().zip(lower).map(|(&v, &l)| v as i128 * l as i128).sum::<i128>();
+            a.push(coeffs);
+            b.push(Q::from_integer(shifted));
+        }
+        for (k, &j) in bounded.iter().enumerate() {
+            let room = upper[j].unwrap() - lower[j];
+            if room < 0 {
+                return Relaxation::Infeasible;
+            }
+            let mut coeffs = vec![Q::zero(); width];
+            coeffs[j] = Q::from_integer(1);
+            coeffs[n + k] = Q::from_integer(1);
+            a.push(coeffs);
+            b.push(Q::from_integer(room as i128));
+        }
+
+        let mut c = self.costs.iter().map(|&v| Q::from_integer(v as i128)).collect::<Vec<Q>>();
+        c.resize(width, Q::zero());
+
+        match simplex(&a, &b, &c) {
+            Relaxation::Optimal(y) => {
+                Relaxation::Optimal((0..n).map(|j| y[j] + Q::from_integer(lower[j] as i128)).collect())
+            }
+            other => other,
+        }
+    }
+
+    // Depth first branch and bound, keeps the cheapest integer solution found so far in search.best
+    fn branch(&self, lower: &mut Vec<i64>, upper: &mut Vec<Option<i64>>, search: &mut Search) {
+        if search.depth >= MAX_DEPTH {
+            search.gave_up = true;
+            return;
+        }
+        search.depth += 1;
+        self.branch_distinct(lower, upper, search);
+        search.depth -= 1;
+    }
+
+    fn branch_distinct(&self, lower: &mut Vec<i64>, upper: &mut Vec<Option<i64>>, search: &mut Search) {
+        if search.gave_up {
+            return;
+        }
+        let Some(k) = self.distinct else {
+            return self.branch_values(lower, upper, search);
+        };
+
+        // Exactly k used: once k variables must be used the rest are 0, once only k can be used they all are
+        let used = (0..self.vars()).filter(|&j| lower[j] > 0).count();
+        let usable = (0..self.vars()).filter(|&j| upper[j] != Some(0)).count();
+        if used > k || usable < k {
+            return;
+        }
+        let (old_lower, old_upper) = (lower.clone(), upper.clone());
+        for j in 0..self.vars() {
+            if used == k && lower[j] == 0 {
+                upper[j] = Some(0);
+            }
+            if usable == k && upper[j] != Some(0) {
+                lower[j] = lower[j].max(1);
+            }
+        }
+        self.branch_values(lower, upper, search);
+        (*lower, *upper) = (old_lower, old_upper);
+    }
+
+    // Branching on fractional values, and for the distinct limit on whether an undecided variable is used
+    fn branch_values(&self, lower: &mut Vec<i64>, upper: &mut Vec<Option<i64>>, search: &mut Search) {
+        let x = match self.relax(lower, upper) {
+            Relaxation::Optimal(x) => x,
+            Relaxation::Infeasible | Relaxation::Unbounded => return,
+        };
+
+        let value = x.iter().zip(&self.costs).map(|(v, &c)| v * Q::from_integer(c as i128)).sum::<Q>();
+        if search.best.as_ref().is_some_and(|(cost, _)| value.ceil().to_integer() >= *cost) {
+            return;
+        }
+
+        // Branch on the most fractional variable, 0/1 switches before everything else, and try the
+        // side it leans towards first
+        let half = Ratio::new(1, 2);
+        let switch = |j: usize| upper[j].is_some_and(|u| u - lower[j] <= 1);
+        let split = (0..x.len())
+            .filter(|&j| !x[j].is_integer())
+            .min_by_key(|&j| (!switch(j), (x[j].fract() - half).abs()));
+
+        let Some(j) = split else {
+            let solution = x.iter().map(|v| v.to_integer() as i64).collect::<Vec<i64>>();
+            let used = solution.iter().filter(|&&v| v > 0).count();
+            match self.distinct {
+                Some(k) if used != k => self.branch_usage(&solution, used > k, lower, upper, search),
+                _ => search.best = Some((value.to_integer(), solution)),
+            }
+            return;
+        };
+
+        let floor = x[j].floor().to_integer() as i64;
+        let (old_lower, old_upper) = (lower[j], upper[j]);
+        let down_first = x[j].fract() < half;
+
+        for down in [down_first, !down_first] {
+            if down {
+                upper[j] = Some(floor);
+            } else {
+                lower[j] = floor + 1;
+            }
+            self.branch(lower, upper, search);
+            (lower[j], upper[j]) = (old_lower, old_upper);
+        }
+    }
+
+    // An integer solution using too many (or too few) variables: pick an undecided one it uses (or
+    // leaves at 0) and try both ways, leaving it as the solution has it first
+    fn branch_usage(
+        &self,
+        x: &[i64],
+        too_many: bool,
+        lower: &mut Vec<i64>,
+        upper: &mut Vec<Option<i64>>,
+        search: &mut Search,
+    ) {
+        let undecided = (0..x.len()).find(|&j| (x[j] > 0) == too_many && lower[j] == 0 && upper[j] != Some(0));
+        let Some(j) = undecided else {
+            return;
+        };
+
+        let (old_lower, old_upper) = (lower[j], upper[j]);
+        for unused in [!too_many, too_many] {
+            if unused {
+                upper[j] = Some(0);
+            } else {
+                lower[j] = 1;
+            }
+            self.branch(lower, upper, search);
+            (lower[j], upper[j]) = (old_lower, old_upper);
+        }
+    }
+}
+
+// Minimises c·x subject to a x = b and x >= 0 with a two phase tableau simplex.
+// Bland's rule (lowest index enters and leaves) keeps it from cycling on degenerate pivots.
+fn simplex(a: &[Vec<Q>], b: &[Q], c: &[Q]) -> Relaxation {
+    let (m, n) = (a.len(), c.len());
+    let rhs = n + m;
+
+    // Phase 1 starts from one artificial variable per row, rows flipped so the right side is >= 0
+    let mut tableau = vec![vec![Q::zero(); rhs + 1]; m];
+    for i in 0..m {
+        let sign = Q::from_integer(if b[i].is_negative() { -1 } else { 1 });
+        for j in 0..n {
+            tableau[i][j] = a[i][j] * sign;
+        }
+        tableau[i][n + i] = Q::from_integer(1);
+        tableau[i][rhs] = b[i] * sign;
+    }
+    let mut basis = (n..n + m).collect::<Vec<usize>>();
+
+    let artificial_costs = (0..n + m).map(|j| Q::from_integer((j >= n) as i128)).collect::<Vec<Q>>();
+    optimise(&mut tableau, &mut basis, &artificial_costs, n + m);
+    let infeasibility = (0..m).filter(|&i| basis[i] >= n).map(|i| tableau[i][rhs]).sum::<Q>();
+    if infeasibility.is_positive() {
+        return Relaxation::Infeasible;
+    }
+
+    // Artificials still in the basis sit at 0, swap them for a real column where the row has one.
+    // Rows without any are redundant and keep their artificial, which may not enter again.
+    for i in 0..m {
+        if basis[i] >= n && let Some(j) = (0..n).find(|&j| !tableau[i][j].is_zero()) {
+            pivot(&mut tableau, &mut basis, i, j);
+        }
+    }
+
+    let mut costs = c.to_vec();
+    costs.resize(n + m, Q::zero());
+    if !optimise(&mut tableau, &mut basis, &costs, n) {
+        return Relaxation::Unbounded;
+    }
+
+    let mut x = vec![Q::zero(); n];
+    for i in 0..m {
+        if basis[i] < n {
+            x[basis[i]] = tableau[i][rhs];
+        }
+    }
+    Relaxation::Optimal(x)
+}
+
+// Simplex iterations where only the first `enterable` columns may enter, false if unbounded
+fn optimise(tableau: &mut [Vec<Q>], basis: &mut [usize], costs: &[Q], enterable: usize) -> bool {
+    let rhs = tableau.first().map_or(0, |row| row.len() - 1);
+    loop {
+        let reduced = |j: usize| costs[j] - (0..tableau.len()).map(|i| costs[basis[i]] * tableau[i][j]).sum::<Q>();
+        let Some(entering) = (0..enterable).find(|&j| !basis.contains(&j) && reduced(j).is_negative()) else {
+            return true;
+        };
+
+        let leaving = (0..tableau.len())
+            .filter(|&i| tableau[i][entering].is_positive())
+            .min_by(|&i, &k| {
+                let (ri, rk) = (tableau[i][rhs] / tableau[i][entering], tableau[k][rhs] / tableau[k][entering]);
+                ri.cmp(&rk).then(basis[i].cmp(&basis[k]))
+            });
+        let Some(leaving) = leaving else {
+            return false;
+        };
+        pivot(tableau, basis, leaving, entering);
+    }
+}
+
+fn pivot(tableau: &mut [Vec<Q>], basis: &mut [usize], row: usize, col: usize) {
+    let inv = tableau[row][col].recip();
+    tableau[row].iter_mut().for_each(|v| *v *= inv);
+
+    let pivot_row = tableau[row].clone();
+    for (i, r) in tableau.iter_mut().enumerate() {
+        if i != row && !r[col].is_zero() {
+            let factor = r[col];
+            r.iter_mut().zip(&pivot_row).for_each(|(v, &p)| *v -= factor * p);
+        }
+    }
+    basis[row] = col;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // The exact solver and microlp have to agree on every program that has an answer
+    fn optimum(program: &IntegerProgram) -> Vec<i64> {
+        let Outcome::Optimal(x) = program.solve() else {
+            panic!("expected an optimum, got {:?}", program.solve());
+        };
+        assert_eq!(program.solve_with(Backend::Microlp), Ok(Outcome::Optimal(x.clone())));
+        x
+    }
+
+    fn reason(program: &IntegerProgram) -> String {
+        match program.solve() {
+            Outcome::Infeasible(reason) => reason,
+            other => panic!("expected no solution, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn infeasible_even_as_fractions() {
+        let negative = IntegerProgram::new(vec![vec![1, 1]], vec![-1]);
+        assert_eq!(reason(&negative), "no non-negative solution, not even a fractional one");
+
+        let contradiction = IntegerProgram::new(vec![vec![1, 1], vec![1, 1]], vec![2, 3]);
+        assert_eq!(reason(&contradiction), "no non-negative solution, not even a fractional one");
+    }
+
+    #[test]
+    fn rows_without_integer_solutions_are_caught_before_branching() {
+        let program = IntegerProgram::new(vec![vec![2, -2]], vec![1]);
+        assert_eq!(reason(&program), "row 1 only has fractional solutions, its coefficients are multiples of 2 but 1 is not");
+
+        // Every row alone has integer solutions, together they say 2x - 2z = 1 with nothing bounding x or z
+        let unbounded = IntegerProgram::new(vec![vec![1, 1, -2], vec![1, -1, 0]], vec![1, 0]);
+        assert!(reason(&unbounded).starts_with("gave up after branching"));
+    }
+
+    #[test]
+    fn only_fractional_solutions() {
+        // Every pair sums to 1, so all three are 1/2
+        let program = IntegerProgram::new(vec![vec![1, 1, 0], vec![0, 1, 1], vec![1, 0, 1]], vec![1, 1, 1]);
+        assert_eq!(reason(&program), "only fractional solutions exist");
+    }
+
+    #[test]
+    fn redundant_rows_are_harmless() {
+        // Row 1 repeats row 0 and row 3 is the sum of rows 0 and 2
+        let program = IntegerProgram::new(
+            vec![vec![1, 1, 0], vec![1, 1, 0], vec![0, 1, 1], vec![1, 2, 1]],
+            vec![3, 3, 4, 7],
+        );
+        assert_eq!(optimum(&program), vec![0, 3, 1]);
+
+        let degenerate = IntegerProgram::new(vec![vec![1, 1], vec![0, 0]], vec![0, 0]);
+        assert_eq!(optimum(&degenerate), vec![0, 0]);
+    }
+
+    #[test]
+    fn upper_bounds_and_costs() {
+        let program = IntegerProgram::new(vec![vec![1, 1]], vec![5]).with_costs(vec![1, 2]);
+        assert_eq!(optimum(&program), vec![5, 0]);
+        assert_eq!(optimum(&program.with_upper_bounds(vec![Some(2), None])), vec![2, 3]);
+
+        let too_tight = IntegerProgram::new(vec![vec![1, 1]], vec![5]).with_upper_bounds(vec![Some(2), Some(2)]);
+        assert_eq!(reason(&too_tight), "no non-negative solution, not even a fractional one");
+
+        let unbounded = IntegerProgram::new(vec![vec![1, -1]], vec![0]).with_costs(vec![-1, 0]);
+        assert_eq!(unbounded.solve(), Outcome::Unbounded);
+    }
+
+    #[test]
+    fn exact_number_of_distinct_variables() {
+        let program = |k| IntegerProgram::new(vec![vec![1, 1, 1]], vec![6])
+            .with_costs(vec![1, 2, 3])
+            .with_upper_bounds(vec![Some(4); 3])
+            .with_distinct(k);
+
+        assert_eq!(optimum(&program(2)), vec![4, 2, 0]);
+        assert_eq!(optimum(&program(3)), vec![4, 1, 1]);
+        assert_eq!(reason(&program(1)), "no integer solution has exactly 1 non-zero variables");
+        assert_eq!(reason(&program(4)), "no integer solution has exactly 4 non-zero variables");
+    }
+}
@@ -0,0 +1,262 @@
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+
+use super::clustering::Edge;
+use super::geometry::{Metric, Point};
+
+// k-d tree over integer points of any dimension. All nodes live in one Vec and refer to
+// each other by index, points are referenced by their index in the slice the tree was built from.
+// Distances come from an integer Metric (squared euclidean by default), so everything stays exact.
+
+const LEAF_SIZE: usize = 8;
+
+enum KdNode {
+    Split { axis: usize, left: usize, right: usize },
+    // Range into KdTree::order
+    Leaf { start: usize, end: usize },
+}
+
+struct NodeData<const D: usize> {
+    node: KdNode,
+    // Bounding box of all points below this node
+    min: [i64; D],
+    max: [i64; D],
+}
+
+pub struct KdTree<const D: usize> {
+    points: Vec<Point<D>>,
+    order: Vec<usize>,
+    nodes: Vec<NodeData<D>>,
+    metric: Metric,
+}
+
+impl<const D: usize> KdTree<D> {
+    pub fn with_metric(points: Vec<Point<D>>, metric: Metric) -> Self {
+        let mut tree = Self {
+            order: (0..points.len()).collect(),
+            points,
+            nodes: vec![],
+            metric,
+        };
+        if !tree.points.is_empty() {
+            tree.build(0, tree.points.len());
+        }
+        tree
+    }
+
+    pub fn len(&self) -> usize {
+        self.points.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.points.is_empty()
+    }
+
+    pub fn distance(&self, a: usize, b: usize) -> i64 {
+        self.metric.distance(&self.points[a], &self.points[b])
+    }
+
+    // Builds the subtree for order[start..end] and returns its node index. Splits at the median
+    // of the widest axis so both halves get the same number of points.
+    fn build(&mut self, start: usize, end: usize) -> usize {
+        let mut min = [i64::MAX; D];
+        let mut max = [i64::MIN; D];
+        for &idx in &self.order[start..end] {
+            for axis in 0..D {
+                min[axis] = min[axis].min(self.points[idx][axis]);
+                max[axis] = max[axis].max(self.points[idx][axis]);
+            }
+        }
+
+        let id = self.nodes.len();
+        self.nodes.push(NodeData { node: KdNode::Leaf { start, end }, min, max });
+
+        let axis = (0..D).max_by_key(|&axis| max[axis] - min[axis]).unwrap_or(0);
+        if end - start <= LEAF_SIZE || D == 0 || max[axis] == min[axis] {
+            return id;
+        }
+
+        let mid = (start + end) / 2;
+        let points = &self.points;
+        self.order[start..end].select_nth_unstable_by_key(mid - start, |&idx| points[idx][axis]);
+
+        let left = self.build(start, mid);
+        let right = self.build(mid, end);
+        self.nodes[id].node = KdNode::Split { axis, left, right };
+        id
+    }
+
+    // Smallest possible distance from query to anything inside the node's box
+    fn box_distance(&self, node: usize, query: &Point<D>) -> i64 {
+        let data = &self.nodes[node];
+        self.metric.combine((0..D).map(|axis| {
+            (data.min[axis] - query[axis]).max(query[axis] - data.max[axis]).max(0)
+        }))
+    }
+
+    // Visits the tree closest subtree first. `visit` gets (dist, idx) of every point in the
+    // leaves that are reached, `bound` says how far away a box may be to still be worth entering.
+    fn search(&self, node: usize, query: &Point<D>, visit: &mut impl FnMut(i64, usize), bound: &impl Fn() -> i64) {
+        if self.box_distance(node, query) > bound() {
+            return;
+        }
+
+        match self.nodes[node].node {
+            KdNode::Leaf { start, end } => {
+                for &idx in &self.order[start..end] {
+                    visit(self.metric.distance(&self.points[idx], query), idx);
+                }
+            }
+            KdNode::Split { axis, left, right } => {
+                let (near, far) = if query[axis] <= self.nodes[left].max[axis] { (left, right) } else { (right, left) };
+                self.search(near, query, visit, bound);
+                self.search(far, query, visit, bound);
+            }
+        }
+    }
+
+    // Nearest neighbour of point idx that comes strictly after `after` in (dist, idx) order.
+    // Calling it repeatedly with the previous answer walks through all other points from near to far.
+    pub fn next_nearest(&self, idx: usize, after: Option<(i64, usize)>) -> Option<(i64, usize)> {
+        if self.is_empty() {
+            return None;
+        }
+
+        let query = self.points[idx];
+        let best = std::cell::Cell::new(None::<(i64, usize)>);
+        self.search(0, &query, &mut |dist, other| {
+            let key = (dist, other);
+            if other != idx && after.is_none_or(|after| key > after) && best.get().is_none_or(|best| key < best) {
+                best.set(Some(key));
+            }
+        }, &|| best.get().map_or(i64::MAX, |(dist, _)| dist));
+
+        best.get()
+    }
+
+    // All pairs of points from closest to farthest, ordered by (dist, a, b) like the dense clustering.
+    // Only keeps one candidate per point around, so taking the first few pairs is cheap.
+    pub fn pairs(&self) -> PairIter<'_, D> {
+        let mut heap = BinaryHeap::with_capacity(self.len());
+        for idx in 0..self.len() {
+            if let Some((dist, other)) = self.next_nearest(idx, None) {
+                heap.push(Reverse((dist, idx.min(other), idx.max(other), idx)));
+            }
+        }
+        PairIter { tree: self, heap }
+    }
+}
+
+// Plain nearest neighbour queries. Day 8 only walks the pairs, so these have no caller outside the tests.
+#[allow(dead_code)]
+impl<const D: usize> KdTree<D> {
+    // The k closest points to query as (dist, idx), closest first, ties by index
+    pub fn knn(&self, query: &Point<D>, k: usize) -> Vec<(i64, usize)> {
+        if k == 0 || self.is_empty() {
+            return vec![];
+        }
+
+        let best = std::cell::RefCell::new(BinaryHeap::with_capacity(k + 1));
+        self.search(0, query, &mut |dist, idx| {
+            let mut best = best.borrow_mut();
+            best.push((dist, idx));
+            if best.len() > k {
+                best.pop();
+            }
+        }, &|| {
+            let best = best.borrow();
+            if best.len() < k { i64::MAX } else { best.peek().map_or(i64::MAX, |&(dist, _)| dist) }
+        });
+
+        best.into_inner().into_sorted_vec()
+    }
+
+    // Every point with distance <= radius, closest first
+    pub fn within_radius(&self, query: &Point<D>, radius: i64) -> Vec<(i64, usize)> {
+        let mut found = vec![];
+        if !self.is_empty() {
+            self.search(0, query, &mut |dist, idx| {
+                if dist <= radius {
+                    found.push((dist, idx));
+                }
+            }, &|| radius);
+        }
+        found.sort_unstable();
+        found
+    }
+}
+
+pub struct PairIter<'a, const D: usize> {
+    tree: &'a KdTree<D>,
+    // (dist, a, b, point whose neighbour list this entry came from)
+    heap: BinaryHeap<Reverse<(i64, usize, usize, usize)>>,
+}
+
+impl<const D: usize> Iterator for PairIter<'_, D> {
+    type Item = Edge;
+
+    fn next(&mut self) -> Option<Edge> {
+        // Every pair shows up once from each of its ends, only report it from the smaller one
+        while let Some(Reverse((dist, a, b, from))) = self.heap.pop() {
+            let other = if from == a { b } else { a };
+            if let Some((next_dist, next)) = self.tree.next_nearest(from, Some((dist, other))) {
+                self.heap.push(Reverse((next_dist, from.min(next), from.max(next), from)));
+            }
+            if from == a {
+                return Some(Edge { a, b, dist });
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::test_points::random_points;
+
+    #[test]
+    fn queries_match_brute_force() {
+        for seed in 0..100u64 {
+            let points = random_points::<3>(seed, 1 + (seed % 50) as usize, [4, 100, 10_000][(seed % 3) as usize]);
+            let metric = ["euclidean", "manhattan", "chebyshev:1,2,3"][(seed % 3) as usize].parse::<Metric>().unwrap();
+            let tree = KdTree::with_metric(points.clone(), metric.clone());
+
+            let query = points[0] + points[points.len() - 1];
+            let mut all = (0..points.len())
+                .map(|idx| (metric.distance(&points[idx], &query), idx))
+                .collect::<Vec<(i64, usize)>>();
+            all.sort_unstable();
+
+            let k = (seed % 7) as usize;
+            assert_eq!(tree.knn(&query, k), all[..k.min(all.len())], "seed {}", seed);
+
+            let radius = all[all.len() / 2].0;
+            let inside = all.iter().copied().filter(|&(dist, _)| dist <= radius).collect::<Vec<(i64, usize)>>();
+            assert_eq!(tree.within_radius(&query, radius), inside, "seed {}", seed);
+        }
+    }
+
+    #[test]
+    fn pairs_come_out_in_brute_force_order() {
+        for seed in 0..100u64 {
+            // Small ranges put plenty of boxes on the same spot
+            let mut points = random_points::<3>(seed, 1 + (seed % 40) as usize, [2, 5, 1000][(seed % 3) as usize]);
+            points.extend_from_within(..points.len() / 4);
+            let metric = ["euclidean", "manhattan", "chebyshev:2,1,1"][(seed % 3) as usize].parse::<Metric>().unwrap();
+            let tree = KdTree::with_metric(points.clone(), metric.clone());
+
+            let mut all = vec![];
+            for a in 0..points.len() {
+                for b in a + 1..points.len() {
+                    all.push(Edge { a, b, dist: metric.distance(&points[a], &points[b]) });
+                }
+            }
+            all.sort_unstable_by_key(|e| (e.dist, e.a, e.b));
+
+            let k = [1, all.len() / 3, all.len()][(seed % 3) as usize];
+            assert_eq!(tree.pairs().take(k).collect::<Vec<Edge>>(), all[..k.min(all.len())], "seed {}", seed);
+            assert_eq!(tree.pairs().count(), all.len(), "seed {}", seed);
+        }
+    }
+}
@@ -0,0 +1,17 @@
+pub mod automaton;
+pub mod render;
+pub mod union_find;
+pub mod clustering;
+pub mod kd_tree;
+pub mod geometry;
+pub mod rectilinear;
+pub mod polygon;
+pub mod corner_rectangles;
+pub mod raster;
+pub mod gf2;
+pub mod integer_program;
+pub mod model_file;
+pub mod worker_pool;
+
+#[cfg(test)]
+mod test_points;
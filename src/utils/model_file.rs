@@ -0,0 +1,601 @@
+use std::collections::HashMap;
+use std::str::FromStr;
+
+use super::integer_program::IntegerProgram;
+
+// Integer programs as LP (CPLEX) and MPS files, so a model can be checked in any other solver.
+// Only what IntegerProgram can express is supported: minimisation, integer variables with a lower
+// bound of 0 and an optional upper bound, and =, <= and >= rows. Every variable is read as integer,
+// whether the file declares it or not. The distinct limit has no file syntax, so it is written
+// the way the good_lp backends see it, as 0/1 variables z with z[j] <= x[j] <= upper[j] * z[j].
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Lp,
+    Mps,
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "lp" => Ok(Format::Lp),
+            "mps" => Ok(Format::Mps),
+            _ => Err(format!("Unknown model format '{}', expected lp or mps", s)),
+        }
+    }
+}
+
+impl Format {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Format::Lp => "lp",
+            Format::Mps => "mps",
+        }
+    }
+
+    // From the file extension, e.g. line003.mps
+    pub fn of_path(path: &str) -> Result<Self, String> {
+        let extension = path.rsplit_once('.').map(|(_, ext)| ext.to_lowercase()).unwrap_or_default();
+        extension.parse().map_err(|_| format!("Can't tell the model format of {}, expected a .lp or .mps file", path))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Relation {
+    Equal,
+    AtMost,
+    AtLeast,
+}
+
+#[derive(Debug, Clone)]
+pub struct Row {
+    pub name: String,
+    // (variable, coefficient)
+    pub terms: Vec<(usize, i64)>,
+    pub relation: Relation,
+    pub rhs: i64,
+}
+
+// A program with names for its variables and rows, as it appears in a file
+#[derive(Debug, Clone, Default)]
+pub struct Model {
+    pub names: Vec<String>,
+    pub costs: Vec<i64>,
+    pub upper: Vec<Option<i64>>,
+    pub rows: Vec<Row>,
+}
+
+impl Model {
+    // Variables x0, x1, ... and rows c0, c1, ..., plus z0, ... and the rows u0, l0, ... and k
+    // when the program limits the number of distinct variables
+    pub fn from_program(program: &IntegerProgram) -> Result<Self, String> {
+        let mut model = Model::default();
+        for (j, (&cost, &upper)) in program.costs().iter().zip(program.upper()).enumerate() {
+            model.variable(&format!("x{}", j));
+            (model.costs[j], model.upper[j]) = (cost, upper);
+        }
+        for (i, (row, &rhs)) in program.matrix().iter().zip(program.rhs()).enumerate() {
+            let terms = row.iter().enumerate().filter(|&(_, &a)| a != 0).map(|(j, &a)| (j, a)).collect();
+            model.rows.push(Row { name: format!("c{}", i), terms, relation: Relation::Equal, rhs });
+        }
+
+        if let Some(k) = program.distinct() {
+            let n = program.vars();
+            let mut used = vec![];
+            for j in 0..n {
+                let bound = program.upper()[j]
+                    .ok_or("Limiting the number of distinct variables needs an upper bound on every variable")?;
+                let z = model.variable(&format!("z{}", j));
+                model.upper[z] = Some(1);
+                model.rows.push(Row { name: format!("u{}", j), terms: vec![(j, 1), (z, -bound)], relation: Relation::AtMost, rhs: 0 });
+                model.rows.push(Row { name: format!("l{}", j), terms: vec![(j, 1), (z, -1)], relation: Relation::AtLeast, rhs: 0 });
+                used.push((z, 1));
+            }
+            model.rows.push(Row { name: "k".to_string(), terms: used, relation: Relation::Equal, rhs: k as i64 });
+        }
+        Ok(model)
+    }
+
+    // Inequalities get a slack variable each, after the named ones, so every named variable keeps its index
+    pub fn to_program(&self) -> IntegerProgram {
+        let slacks = self.rows.iter().filter(|row| row.relation != Relation::Equal).count();
+        let width = self.names.len() + slacks;
+
+        let mut matrix = vec![];
+        let mut slack = self.names.len();
+        for row in &self.rows {
+            let mut coeffs = vec![0; width];
+            for &(j, a) in &row.terms {
+                coeffs[j] += a;
+            }
+            match row.relation {
+                Relation::Equal => {}
+                Relation::AtMost | Relation::AtLeast => {
+                    coeffs[slack] = if row.relation == Relation::AtMost { 1 } else { -1 };
+                    slack += 1;
+                }
+            }
+            matrix.push(coeffs);
+        }
+
+        let mut costs = self.costs.clone();
+        costs.resize(width, 0);
+        let mut upper = self.upper.clone();
+        upper.resize(width, None);
+        IntegerProgram::new(matrix, self.rows.iter().map(|row| row.rhs).collect())
+            .with_costs(costs)
+            .with_upper_bounds(upper)
+    }
+
+    // Index of a variable, added with cost 0 and no upper bound the first time it is seen
+    fn variable(&mut self, name: &str) -> usize {
+        if let Some(j) = self.names.iter().position(|n| n == name) {
+            return j;
+        }
+        self.names.push(name.to_string());
+        self.costs.push(0);
+        self.upper.push(None);
+        self.names.len() - 1
+    }
+
+    pub fn write(&self, format: Format, title: &str) -> String {
+        match format {
+            Format::Lp => self.to_lp(title),
+            Format::Mps => self.to_mps(title),
+        }
+    }
+
+    pub fn read(format: Format, text: &str) -> Result<Self, String> {
+        match format {
+            Format::Lp => Self::parse_lp(text),
+            Format::Mps => Self::parse_mps(text),
+        }
+    }
+
+    fn expression(&self, terms: &[(usize, i64)]) -> String {
+        let mut out = String::new();
+        for &(j, a) in terms.iter().filter(|&&(_, a)| a != 0) {
+            out += match (out.is_empty(), a < 0) {
+                (true, false) => "",
+                (true, true) => "-",
+                (false, false) => " + ",
+                (false, true) => " - ",
+            };
+            if a.abs() != 1 {
+                out += &format!("{} ", a.abs());
+            }
+            out += &self.names[j];
+        }
+        // An empty side still needs a variable
+        if out.is_empty() {
+            out = format!("0 {}", self.names.first().map_or("x0", |n| n.as_str()));
+        }
+        out
+    }
+
+    fn to_lp(&self, title: &str) -> String {
+        let mut out = format!("\\ {}\nMinimize\n", title);
+        let objective = self.costs.iter().copied().enumerate().collect::<Vec<(usize, i64)>>();
+        out += &format!(" obj: {}\n", self.expression(&objective));
+
+        out += "Subject To\n";
+        for row in &self.rows {
+            let op = match row.relation {
+                Relation::Equal => "=",
+                Relation::AtMost => "<=",
+                Relation::AtLeast => ">=",
+            };
+            out += &format!(" {}: {} {} {}\n", row.name, self.expression(&row.terms), op, row.rhs);
+        }
+
+        out += "Bounds\n";
+        for (name, upper) in self.names.iter().zip(&self.upper) {
+            match upper {
+                Some(upper) => out += &format!(" 0 <= {} <= {}\n", name, upper),
+                None => out += &format!(" {} >= 0\n", name),
+            }
+        }
+
+        out += "Generals\n";
+        out += &format!(" {}\n", self.names.join(" "));
+        out += "End\n";
+        out
+    }
+
+    fn to_mps(&self, title: &str) -> String {
+        let mut out = format!("NAME {}\nROWS\n N obj\n", title.split_whitespace().collect::<Vec<&str>>().join("_"));
+        for row in &self.rows {
+            let kind = match row.relation {
+                Relation::Equal => "E",
+                Relation::AtMost => "L",
+                Relation::AtLeast => "G",
+            };
+            out += &format!(" {} {}\n", kind, row.name);
+        }
+
+        // Integer columns sit between the markers, quoted like every other MPS writer does it
+        out += "COLUMNS\n    MARKER 'MARKER' 'INTORG'\n";
+        for (j, name) in self.names.iter().enumerate() {
+            out += &format!("    {} obj {}\n", name, self.costs[j]);
+            for row in &self.rows {
+                for &(_, a) in row.terms.iter().filter(|&&(v, a)| v == j && a != 0) {
+                    out += &format!("    {} {} {}\n", name, row.name, a);
+                }
+            }
+        }
+        out += "    MARKER 'MARKER' 'INTEND'\n";
+
+        out += "RHS\n";
+        for row in self.rows.iter().filter(|row| row.rhs != 0) {
+            out += &format!("    RHS {} {}\n", row.name, row.rhs);
+        }
+
+        out += "BOUNDS\n";
+        for (name, upper) in self.names.iter().zip(&self.upper) {
+            match upper {
+                Some(upper) => out += &format!(" UP BND {} {}\n", name, upper),
+                None => out += &format!(" PL BND {}\n", name),
+            }
+        }
+        out += "ENDATA\n";
+        out
+    }
+
+    fn parse_lp(text: &str) -> Result<Self, String> {
+        // Sections by their keyword, comments start with a backslash
+        let mut sections: HashMap<&str, Vec<String>> = HashMap::new();
+        let mut section = "";
+        for line in text.lines() {
+            let line = line.split('\\').next().unwrap_or("").trim();
+            let keyword = match line.to_lowercase().as_str() {
+                "minimize" | "minimise" | "minimum" | "min" => Some("objective"),
+                "maximize" | "maximise" | "maximum" | "max" => return Err("Only minimisation is supported".to_string()),
+                "subject to" | "such that" | "st" | "s.t." | "st." => Some("rows"),
+                "bounds" | "bound" => Some("bounds"),
+                "generals" | "general" | "gen" | "integers" | "integer" => Some("generals"),
+                "binaries" | "binary" | "bin" => Some("binaries"),
+                "semi-continuous" | "semis" | "semi" | "sos" => return Err(format!("Section '{}' is not supported", line)),
+                "end" => break,
+                _ => None,
+            };
+            match keyword {
+                Some(keyword) => section = keyword,
+                None if line.is_empty() => {}
+                None if section.is_empty() => return Err(format!("'{}' before the objective", line)),
+                None => sections.entry(section).or_default().push(line.to_string()),
+            }
+        }
+
+        let mut model = Model::default();
+        let tokens = |section: &str| sections.get(section).map(|lines| tokenize(&lines.join(" "))).unwrap_or_default();
+
+        let objective = tokens("objective");
+        let start = if objective.get(1).is_some_and(|t| t == ":") { 2 } else { 0 };
+        for (j, a) in model.terms(&objective[start..])? {
+            model.costs[j] += a;
+        }
+
+        // Rows can span lines, so they are read from one token stream: [name:] terms op rhs
+        let rows = tokens("rows");
+        let mut pos = 0;
+        while pos < rows.len() {
+            let name = if rows.get(pos + 1).is_some_and(|t| t == ":") {
+                pos += 2;
+                rows[pos - 2].clone()
+            } else {
+                format!("r{}", model.rows.len())
+            };
+            let op = (pos..rows.len()).find(|&p| relation(&rows[p]).is_some()).ok_or(format!("Row {} has no relation", name))?;
+            let terms = model.terms(&rows[pos..op])?;
+            let (rhs, next) = signed_number(&rows, op + 1).ok_or(format!("Row {} has no right hand side", name))?;
+            model.rows.push(Row { name, terms, relation: relation(&rows[op]).unwrap(), rhs });
+            pos = next;
+        }
+
+        for line in sections.get("bounds").into_iter().flatten() {
+            model.bound(&tokenize(line))?;
+        }
+        for name in tokens("generals") {
+            model.variable(&name);
+        }
+        for name in tokens("binaries") {
+            let j = model.variable(&name);
+            model.upper[j] = Some(model.upper[j].map_or(1, |u| u.min(1)));
+        }
+        Ok(model)
+    }
+
+    // [sign] [coefficient] name, repeated
+    fn terms(&mut self, tokens: &[String]) -> Result<Vec<(usize, i64)>, String> {
+        let mut terms = vec![];
+        let mut pos = 0;
+        while pos < tokens.len() {
+            let mut sign = 1;
+            while tokens[pos] == "+" || tokens[pos] == "-" {
+                sign *= if tokens[pos] == "-" { -1 } else { 1 };
+                pos += 1;
+                if pos == tokens.len() {
+                    return Err("Expression ends in a sign".to_string());
+                }
+            }
+            let mut coeff = 1;
+            if is_number(&tokens[pos]) {
+                coeff = integer(&tokens[pos])?;
+                pos += 1;
+            }
+            let name = tokens.get(pos).filter(|t| is_name(t)).ok_or("Constant terms are not supported".to_string())?;
+            terms.push((self.variable(name), sign * coeff));
+            pos += 1;
+        }
+        Ok(terms)
+    }
+
+    // x >= 0, x <= u, 0 <= x <= u, x = 0 and the same mirrored
+    fn bound(&mut self, tokens: &[String]) -> Result<(), String> {
+        let line = tokens.join(" ");
+        let unsupported = || format!("Unsupported bound '{}', variables start at 0 and may have an upper bound", line);
+
+        let (name, op, value) = match tokens {
+            [lo, first, name, second, hi] if is_number(lo) && is_name(name) => {
+                let (Some(first), Some(second)) = (relation(first), relation(second)) else {
+                    return Err(unsupported());
+                };
+                if first != second || first == Relation::Equal {
+                    return Err(unsupported());
+                }
+                let (lo, hi) = if first == Relation::AtMost { (lo, hi) } else { (hi, lo) };
+                if integer(lo)? != 0 {
+                    return Err(unsupported());
+                }
+                (name, Relation::AtMost, integer(hi)?)
+            }
+            [name, op, value] if is_name(name) => (name, relation(op).ok_or_else(unsupported)?, integer(value)?),
+            [value, op, name] if is_name(name) => {
+                let mirrored = match relation(op).ok_or_else(unsupported)? {
+                    Relation::AtMost => Relation::AtLeast,
+                    Relation::AtLeast => Relation::AtMost,
+                    Relation::Equal => Relation::Equal,
+                };
+                (name, mirrored, integer(value)?)
+            }
+            _ => return Err(unsupported()),
+        };
+
+        let j = self.variable(name);
+        match op {
+            Relation::AtMost if value >= 0 => self.upper[j] = Some(value),
+            Relation::AtLeast | Relation::Equal if value == 0 => {
+                if op == Relation::Equal {
+                    self.upper[j] = Some(0);
+                }
+            }
+            _ => return Err(unsupported()),
+        }
+        Ok(())
+    }
+
+    fn parse_mps(text: &str) -> Result<Self, String> {
+        let mut model = Model::default();
+        let mut objective = None;
+        let mut row_index: HashMap<String, usize> = HashMap::new();
+        let mut section = String::new();
+
+        for (idx, line) in text.lines().enumerate() {
+            if line.trim().is_empty() || line.starts_with('*') {
+                continue;
+            }
+            let fields = line.split_whitespace().collect::<Vec<&str>>();
+            let error = |msg: &str| format!("Line {}: {}", idx + 1, msg);
+
+            // Section headers start in the first column, data lines are indented
+            if !line.starts_with(char::is_whitespace) {
+                section = fields[0].to_uppercase();
+                match section.as_str() {
+                    "NAME" | "ROWS" | "COLUMNS" | "RHS" | "BOUNDS" => continue,
+                    "ENDATA" => break,
+                    "OBJSENSE" => return Err(error("OBJSENSE is not supported, the objective is minimised")),
+                    other => return Err(error(&format!("section {} is not supported", other))),
+                }
+            }
+
+            match section.as_str() {
+                "ROWS" => {
+                    let [kind, name] = fields[..] else {
+                        return Err(error("expected a row type and a name"));
+                    };
+                    let relation = match kind.to_uppercase().as_str() {
+                        "N" => {
+                            objective.get_or_insert(name.to_string());
+                            continue;
+                        }
+                        "E" => Relation::Equal,
+                        "L" => Relation::AtMost,
+                        "G" => Relation::AtLeast,
+                        _ => return Err(error(&format!("unknown row type {}", kind))),
+                    };
+                    row_index.insert(name.to_string(), model.rows.len());
+                    model.rows.push(Row { name: name.to_string(), terms: vec![], relation, rhs: 0 });
+                }
+                "COLUMNS" => {
+                    if fields.iter().any(|f| f.trim_matches('\'') == "MARKER") {
+                        continue;
+                    }
+                    let j = model.variable(fields[0]);
+                    for pair in fields[1..].chunks(2) {
+                        let [row, value] = pair else {
+                            return Err(error("row without a value"));
+                        };
+                        let value = integer(value).map_err(|e| error(&e))?;
+                        if objective.as_deref() == Some(*row) {
+                            model.costs[j] += value;
+                        } else if let Some(&i) = row_index.get(*row) {
+                            model.rows[i].terms.push((j, value));
+                        } else {
+                            return Err(error(&format!("unknown row {}", row)));
+                        }
+                    }
+                }
+                "RHS" => {
+                    // The set name is optional in free MPS
+                    let pairs = if fields.len() % 2 == 1 { &fields[1..] } else { &fields[..] };
+                    for pair in pairs.chunks(2) {
+                        let [row, value] = pair else {
+                            return Err(error("row without a value"));
+                        };
+                        if objective.as_deref() == Some(*row) {
+                            continue;
+                        }
+                        let &i = row_index.get(*row).ok_or(error(&format!("unknown row {}", row)))?;
+                        model.rows[i].rhs = integer(value).map_err(|e| error(&e))?;
+                    }
+                }
+                "BOUNDS" => {
+                    let kind = fields[0].to_uppercase();
+                    let (name, value) = match fields.len() {
+                        3 if kind == "BV" || kind == "PL" => (fields[2], None),
+                        2 if kind == "BV" || kind == "PL" => (fields[1], None),
+                        4 => (fields[2], Some(integer(fields[3]).map_err(|e| error(&e))?)),
+                        _ => return Err(error("expected a bound type, a set name, a column and a value")),
+                    };
+                    let j = model.variable(name);
+                    match (kind.as_str(), value) {
+                        ("UP" | "UI", Some(v)) if v >= 0 => model.upper[j] = Some(v),
+                        ("LO" | "LI", Some(0)) | ("PL", None) => {}
+                        ("FX", Some(0)) => model.upper[j] = Some(0),
+                        ("BV", None) => model.upper[j] = Some(1),
+                        _ => return Err(error("unsupported bound, variables start at 0 and may have an upper bound")),
+                    }
+                }
+                _ => return Err(error("data outside of a section")),
+            }
+        }
+        Ok(model)
+    }
+}
+
+// Operators, colons and everything in between as separate tokens
+fn tokenize(text: &str) -> Vec<String> {
+    let mut tokens = vec![];
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            c if c.is_whitespace() => {}
+            '+' | '-' | ':' => tokens.push(c.to_string()),
+            '<' | '>' | '=' => {
+                let mut op = c.to_string();
+                if let Some(&next) = chars.peek().filter(|&&n| matches!(n, '<' | '>' | '=')) {
+                    op.push(next);
+                    chars.next();
+                }
+                tokens.push(op);
+            }
+            _ => {
+                let mut word = c.to_string();
+                // The sign of an exponent like 1e+05 belongs to the number
+                let exponent = |word: &str, n: char| is_number(word) && word.ends_with(['e', 'E']) && (n == '+' || n == '-');
+                while let Some(&next) = chars.peek().filter(|&&n| !n.is_whitespace() && (!"+-:<>=".contains(n) || exponent(&word, n))) {
+                    word.push(next);
+                    chars.next();
+                }
+                tokens.push(word);
+            }
+        }
+    }
+    tokens
+}
+
+fn relation(token: &str) -> Option<Relation> {
+    match token {
+        "=" | "==" => Some(Relation::Equal),
+        "<=" | "=<" | "<" => Some(Relation::AtMost),
+        ">=" | "=>" | ">" => Some(Relation::AtLeast),
+        _ => None,
+    }
+}
+
+fn is_number(token: &str) -> bool {
+    token.starts_with(|c: char| c.is_ascii_digit() || c == '.')
+}
+
+fn is_name(token: &str) -> bool {
+    !is_number(token) && relation(token).is_none() && !matches!(token, "+" | "-" | ":")
+}
+
+// Files written by other tools often say 3.0 or 1e+00, which is fine as long as it is a whole number
+fn integer(token: &str) -> Result<i64, String> {
+    let value = token.parse::<f64>().map_err(|_| format!("Invalid number '{}'", token))?;
+    if value.fract() != 0.0 || value.abs() > i64::MAX as f64 {
+        return Err(format!("'{}' is not an integer", token));
+    }
+    Ok(value as i64)
+}
+
+// Right hand side at pos, which may come with its own sign, and the position after it
+fn signed_number(tokens: &[String], pos: usize) -> Option<(i64, usize)> {
+    match tokens.get(pos)?.as_str() {
+        "-" => Some((-integer(tokens.get(pos + 1)?).ok()?, pos + 2)),
+        "+" => Some((integer(tokens.get(pos + 1)?).ok()?, pos + 2)),
+        token => Some((integer(token).ok()?, pos + 1)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::integer_program::Outcome;
+
+    fn cost(program: &IntegerProgram, outcome: &Outcome) -> Option<i64> {
+        match outcome {
+            Outcome::Optimal(x) => Some(program.costs().iter().zip(x).map(|(c, v)| c * v).sum()),
+            _ => None,
+        }
+    }
+
+    // Write, read back and solve, the optimum has to stay the same
+    fn round_trip(program: &IntegerProgram) {
+        let expected = cost(program, &program.solve());
+        assert!(expected.is_some());
+        let model = Model::from_program(program).unwrap();
+
+        for format in [Format::Lp, Format::Mps] {
+            let text = model.write(format, "round trip");
+            let read = Model::read(format, &text).unwrap();
+            assert_eq!(read.names, model.names, "{:?}:\n{}", format, text);
+
+            let imported = read.to_program();
+            let outcome = imported.solve();
+            assert_eq!(cost(&imported, &outcome), expected, "{:?}:\n{}", format, text);
+            if let Outcome::Optimal(x) = outcome {
+                let x = &x[..program.vars()];
+                for (row, &rhs) in program.matrix().iter().zip(program.rhs()) {
+                    assert_eq!(row.iter().zip(x).map(|(a, v)| a * v).sum::<i64>(), rhs);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn round_trip_with_costs_and_bounds() {
+        let program = IntegerProgram::new(vec![vec![1, 1, 0, 1], vec![0, 1, 1, 1], vec![1, 0, 1, 0]], vec![7, 5, 6])
+            .with_costs(vec![2, 1, 3, 0])
+            .with_upper_bounds(vec![Some(5), None, Some(2), Some(1)]);
+        round_trip(&program);
+    }
+
+    #[test]
+    fn round_trip_with_distinct_limit() {
+        let program = IntegerProgram::new(vec![vec![1, 1, 0, 1], vec![0, 1, 1, 1]], vec![7, 9])
+            .with_upper_bounds(vec![Some(7), Some(7), Some(9), Some(7)])
+            .with_distinct(3);
+        round_trip(&program);
+    }
+
+    #[test]
+    fn mps_markers_are_quoted() {
+        let program = IntegerProgram::new(vec![vec![1, 1]], vec![2]);
+        let text = Model::from_program(&program).unwrap().write(Format::Mps, "markers");
+        assert!(text.contains("MARKER 'MARKER' 'INTORG'") && text.contains("MARKER 'MARKER' 'INTEND'"));
+    }
+}
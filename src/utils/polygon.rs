@@ -0,0 +1,485 @@
+use super::geometry::Point2;
+
+// Exact integer geometry for orthogonal polygons (every edge is horizontal or vertical), alone or with holes.
+// The polygon is the closed region bounded by the vertex loop. Points exactly on an edge are
+// reported as Boundary and count as contained, which is what tile puzzles want: the loop goes
+// through the tile centres and the tiles on it are part of the shape.
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Location {
+    Inside,
+    Boundary,
+    Outside,
+}
+
+#[derive(Debug, Clone)]
+pub struct OrthoPolygon {
+    vertices: Vec<Point2>,
+}
+
+impl OrthoPolygon {
+    pub fn new(vertices: Vec<Point2>) -> Result<Self, String> {
+        if vertices.len() < 4 {
+            return Err(format!("An orthogonal polygon needs at least 4 vertices, got {}", vertices.len()));
+        }
+        for i in 0..vertices.len() {
+            let (a, b) = (vertices[i], vertices[(i + 1) % vertices.len()]);
+            if a.x() != b.x() && a.y() != b.y() {
+                return Err(format!("Edge from {} to {} is not axis aligned", a, b));
+            }
+        }
+        Ok(Self { vertices })
+    }
+
+    pub fn vertices(&self) -> &[Point2] {
+        &self.vertices
+    }
+
+    pub fn edges(&self) -> impl Iterator<Item = (Point2, Point2)> + '_ {
+        let n = self.vertices.len();
+        (0..n).map(move |i| (self.vertices[i], self.vertices[(i + 1) % n]))
+    }
+
+    // Shoelace formula, positive for counter clockwise loops (with y pointing up)
+    pub fn twice_signed_area(&self) -> i64 {
+        self.edges().map(|(a, b)| a.x() * b.y() - b.x() * a.y()).sum()
+    }
+
+    // Orthogonal integer polygons always have an integer area
+    pub fn area(&self) -> i64 {
+        self.twice_signed_area().abs() / 2
+    }
+
+    pub fn perimeter(&self) -> i64 {
+        self.edges().map(|(a, b)| (b.x() - a.x()).abs() + (b.y() - a.y()).abs()).sum()
+    }
+
+    // Every unit step along an axis aligned edge ends on a lattice point, so this is just the perimeter
+    pub fn boundary_points(&self) -> i64 {
+        self.perimeter()
+    }
+
+    // Pick's theorem: A = I + B / 2 - 1
+    pub fn interior_points(&self) -> i64 {
+        self.area() - self.boundary_points() / 2 + 1
+    }
+
+    // Lattice points inside or on the loop, e.g. all red and green tiles of Day 9
+    pub fn lattice_points(&self) -> i64 {
+        self.interior_points() + self.boundary_points()
+    }
+
+    pub fn locate(&self, p: &Point2) -> Location {
+        locate_doubled(self.edges(), 2 * p.x(), 2 * p.y())
+    }
+}
+
+// Several disjoint loops, a loop nested inside an odd number of others is a hole. The loops themselves
+// belong to the region, holes included, so a rectangle may touch a hole's boundary but not its inside.
+#[derive(Debug, Clone)]
+pub struct OrthoRegion {
+    loops: Vec<OrthoPolygon>,
+    // Number of other loops around each loop
+    depth: Vec<usize>,
+}
+
+impl OrthoRegion {
+    // The loops must not touch each other, otherwise the nesting is ambiguous
+    pub fn new(loops: Vec<OrthoPolygon>) -> Self {
+        let depth = loops.iter().enumerate()
+            .map(|(i, inner)| {
+                let probe = inner.vertices()[0];
+                loops.iter().enumerate().filter(|&(j, outer)| j != i && outer.locate(&probe) == Location::Inside).count()
+            })
+            .collect();
+        Self { loops, depth }
+    }
+
+    pub fn loops(&self) -> &[OrthoPolygon] {
+        &self.loops
+    }
+
+    pub fn is_hole(&self, idx: usize) -> bool {
+        self.depth[idx] % 2 == 1
+    }
+
+    pub fn vertices(&self) -> impl Iterator<Item = &Point2> + '_ {
+        self.loops.iter().flat_map(|l| l.vertices())
+    }
+
+    // The boundary of a hole stays part of the region, only its interior points are missing
+    pub fn lattice_points(&self) -> i64 {
+        (0..self.loops.len())
+            .map(|i| if self.is_hole(i) { -self.loops[i].interior_points() } else { self.loops[i].lattice_points() })
+            .sum()
+    }
+}
+
+// Exact containment with continuous edges. Day 9 works on whole tiles and asks RectilinearPolygon
+// instead, these stay for shapes where the edges themselves are what matters.
+#[allow(dead_code)]
+impl OrthoPolygon {
+    pub fn contains_point(&self, p: &Point2) -> bool {
+        self.locate(p) != Location::Outside
+    }
+
+    // Is the closed rectangle spanned by the corners a and b fully inside the closed polygon
+    pub fn contains_rect(&self, a: &Point2, b: &Point2) -> bool {
+        contains_rect(&|| self.edges(), a, b)
+    }
+}
+
+#[allow(dead_code)]
+impl OrthoRegion {
+    pub fn edges(&self) -> impl Iterator<Item = (Point2, Point2)> + '_ {
+        self.loops.iter().flat_map(|l| l.edges())
+    }
+
+    pub fn area(&self) -> i64 {
+        (0..self.loops.len())
+            .map(|i| if self.is_hole(i) { -self.loops[i].area() } else { self.loops[i].area() })
+            .sum()
+    }
+
+    // Even-odd rule over all loops at once
+    pub fn locate(&self, p: &Point2) -> Location {
+        locate_doubled(self.edges(), 2 * p.x(), 2 * p.y())
+    }
+
+    pub fn contains_point(&self, p: &Point2) -> bool {
+        self.locate(p) != Location::Outside
+    }
+
+    pub fn contains_rect(&self, a: &Point2, b: &Point2) -> bool {
+        contains_rect(&|| self.edges(), a, b)
+    }
+}
+
+// Point in polygon for coordinates scaled by two, so cell centres and edge midpoints are exact too.
+// Casts a ray towards +x and counts vertical edges, half open in y so vertices are counted once.
+fn locate_doubled(edges: impl Iterator<Item = (Point2, Point2)>, px: i64, py: i64) -> Location {
+    let mut inside = false;
+
+    for (a, b) in edges {
+        let (ax, ay, bx, by) = (2 * a.x(), 2 * a.y(), 2 * b.x(), 2 * b.y());
+        let (x_lo, x_hi) = (ax.min(bx), ax.max(bx));
+        let (y_lo, y_hi) = (ay.min(by), ay.max(by));
+
+        if x_lo <= px && px <= x_hi && y_lo <= py && py <= y_hi {
+            return Location::Boundary;
+        }
+        if ax == bx && ax > px && y_lo <= py && py < y_hi {
+            inside = !inside;
+        }
+    }
+
+    if inside { Location::Inside } else { Location::Outside }
+}
+
+// Is the closed rectangle spanned by a and b inside the closed shape bounded by the edges
+fn contains_rect<I>(edges: &impl Fn() -> I, a: &Point2, b: &Point2) -> bool
+where
+    I: Iterator<Item = (Point2, Point2)>,
+{
+    let (min_x, max_x) = (a.x().min(b.x()), a.x().max(b.x()));
+    let (min_y, max_y) = (a.y().min(b.y()), a.y().max(b.y()));
+
+    if min_x == max_x || min_y == max_y {
+        return contains_segment(edges, min_x, min_y, max_x, max_y);
+    }
+
+    // If no edge passes through the open rectangle, it is either completely inside or completely
+    // outside, and its centre tells which. The closed rectangle then follows since the shape is closed.
+    let crosses = edges().any(|(p, q)| {
+        let (x_lo, x_hi) = (p.x().min(q.x()), p.x().max(q.x()));
+        let (y_lo, y_hi) = (p.y().min(q.y()), p.y().max(q.y()));
+        if p.x() == q.x() {
+            min_x < p.x() && p.x() < max_x && y_lo < max_y && y_hi > min_y
+        } else {
+            min_y < p.y() && p.y() < max_y && x_lo < max_x && x_hi > min_x
+        }
+    });
+
+    !crosses && locate_doubled(edges(), min_x + max_x, min_y + max_y) != Location::Outside
+}
+
+// Axis aligned segment (or single point). Along it the shape can only start or stop at vertex
+// coordinates, so cut it there and check the midpoint of every piece. The cut points themselves
+// lie between two contained pieces and are then contained as well.
+fn contains_segment<I>(edges: &impl Fn() -> I, min_x: i64, min_y: i64, max_x: i64, max_y: i64) -> bool
+where
+    I: Iterator<Item = (Point2, Point2)>,
+{
+    if min_x == max_x && min_y == max_y {
+        return locate_doubled(edges(), 2 * min_x, 2 * min_y) != Location::Outside;
+    }
+
+    let horizontal = min_y == max_y;
+    let (lo, hi) = if horizontal { (min_x, max_x) } else { (min_y, max_y) };
+
+    let mut cuts = vec![lo, hi];
+    cuts.extend(edges()
+        .map(|(p, _)| if horizontal { p.x() } else { p.y() })
+        .filter(|&along| lo < along && along < hi));
+    cuts.sort_unstable();
+    cuts.dedup();
+
+    cuts.windows(2).all(|w| {
+        let mid = w[0] + w[1];
+        let location = if horizontal {
+            locate_doubled(edges(), mid, 2 * min_y)
+        } else {
+            locate_doubled(edges(), 2 * min_x, mid)
+        };
+        location != Location::Outside
+    })
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Winding {
+    // With y pointing up, i.e. the shoelace sum is negative
+    Clockwise,
+    CounterClockwise,
+}
+
+// Something wrong with a vertex loop. Vertices are given by their index in the loop,
+// edge i runs from vertex i to vertex i + 1 (and the last one back to vertex 0).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Defect {
+    NotAxisAligned { edge: usize },
+    DuplicateVertex { first: usize, again: usize },
+    // Vertex in the middle of a straight run, harmless but it isn't a corner
+    Collinear { vertex: usize },
+    // Two edges touch or cross, or an edge doubles back onto the previous one
+    SelfIntersection { edge_a: usize, edge_b: usize },
+}
+
+impl Defect {
+    // Collinear runs still describe a proper polygon, everything else doesn't
+    pub fn is_error(&self) -> bool {
+        !matches!(self, Defect::Collinear { .. })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Validation {
+    pub defects: Vec<Defect>,
+    // None if the loop encloses no area at all
+    pub winding: Option<Winding>,
+}
+
+impl Validation {
+    #[allow(dead_code)]
+    pub fn is_valid(&self) -> bool {
+        !self.defects.iter().any(|d| d.is_error())
+    }
+}
+
+// Checks that the vertices form a simple closed loop of axis aligned edges. Works on any
+// vertex list, so it can run before OrthoPolygon::new and tell what exactly is wrong.
+pub fn validate(vertices: &[Point2]) -> Validation {
+    let n = vertices.len();
+    let mut defects = vec![];
+    let edge = |i: usize| (vertices[i], vertices[(i + 1) % n]);
+
+    for i in 0..n {
+        let (a, b) = edge(i);
+        if a.x() != b.x() && a.y() != b.y() {
+            defects.push(Defect::NotAxisAligned { edge: i });
+        }
+    }
+
+    let mut seen = std::collections::HashMap::new();
+    for (i, p) in vertices.iter().enumerate() {
+        if let Some(&first) = seen.get(p) {
+            defects.push(Defect::DuplicateVertex { first, again: i });
+        } else {
+            seen.insert(*p, i);
+        }
+    }
+
+    for i in 0..n {
+        let (prev, p, next) = (vertices[(i + n - 1) % n], vertices[i], vertices[(i + 1) % n]);
+        if prev == p || p == next || n < 3 {
+            continue;
+        }
+        if cross(&prev, &p, &next) == 0 {
+            // Going on in the same direction is only redundant, turning back runs over the last edge
+            if (p - prev).dot(&(next - p)) > 0 {
+                defects.push(Defect::Collinear { vertex: i });
+            } else {
+                defects.push(Defect::SelfIntersection { edge_a: (i + n - 1) % n, edge_b: i });
+            }
+        }
+    }
+
+    // Edges next to each other share a vertex, which is fine and handled above
+    for i in 0..n {
+        for j in i + 2..n {
+            if i == 0 && j == n - 1 {
+                continue;
+            }
+            let ((a, b), (c, d)) = (edge(i), edge(j));
+            if a != b && c != d && segments_touch(&a, &b, &c, &d) {
+                defects.push(Defect::SelfIntersection { edge_a: i, edge_b: j });
+            }
+        }
+    }
+
+    let twice_area = (0..n).map(|i| {
+        let (a, b) = edge(i);
+        a.x() * b.y() - b.x() * a.y()
+    }).sum::<i64>();
+    let winding = match twice_area.signum() {
+        1 => Some(Winding::CounterClockwise),
+        -1 => Some(Winding::Clockwise),
+        _ => None,
+    };
+
+    Validation { defects, winding }
+}
+
+// Pairs of edges (index in a, index in b) where two separate loops touch or cross
+pub fn crossings(a: &[Point2], b: &[Point2]) -> Vec<(usize, usize)> {
+    let mut found = vec![];
+    for i in 0..a.len() {
+        let (p, q) = (a[i], a[(i + 1) % a.len()]);
+        for j in 0..b.len() {
+            let (r, s) = (b[j], b[(j + 1) % b.len()]);
+            if segments_touch(&p, &q, &r, &s) {
+                found.push((i, j));
+            }
+        }
+    }
+    found
+}
+
+// Orientation of c relative to the line a -> b, in i128 so large coordinates can't overflow
+fn cross(a: &Point2, b: &Point2, c: &Point2) -> i128 {
+    let (abx, aby) = ((b.x() - a.x()) as i128, (b.y() - a.y()) as i128);
+    let (acx, acy) = ((c.x() - a.x()) as i128, (c.y() - a.y()) as i128);
+    abx * acy - aby * acx
+}
+
+// c lies within the bounding box of a and b (only meaningful if the three are collinear)
+fn within_box(a: &Point2, b: &Point2, c: &Point2) -> bool {
+    a.x().min(b.x()) <= c.x() && c.x() <= a.x().max(b.x()) && a.y().min(b.y()) <= c.y() && c.y() <= a.y().max(b.y())
+}
+
+// Do the closed segments a-b and c-d have any point in common
+fn segments_touch(a: &Point2, b: &Point2, c: &Point2, d: &Point2) -> bool {
+    let (d1, d2) = (cross(c, d, a).signum(), cross(c, d, b).signum());
+    let (d3, d4) = (cross(a, b, c).signum(), cross(a, b, d).signum());
+
+    if d1 * d2 < 0 && d3 * d4 < 0 {
+        return true;
+    }
+    (d1 == 0 && within_box(c, d, a)) || (d2 == 0 && within_box(c, d, b))
+        || (d3 == 0 && within_box(a, b, c)) || (d4 == 0 && within_box(a, b, d))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::raster::CellGrid;
+
+    fn polygon(coords: &[(i64, i64)]) -> OrthoPolygon {
+        OrthoPolygon::new(coords.iter().map(|&(x, y)| Point2::new(x, y)).collect()).unwrap()
+    }
+
+    #[test]
+    fn lattice_points_count_tiles() {
+        let l_shape = polygon(&[(0, 0), (4, 0), (4, 2), (2, 2), (2, 4), (0, 4)]);
+        assert_eq!((l_shape.area(), l_shape.perimeter()), (12, 16));
+        assert_eq!((l_shape.interior_points(), l_shape.lattice_points()), (5, 21));
+
+        let region = OrthoRegion::new(vec![
+            polygon(&[(0, 0), (0, 6), (6, 6), (6, 0)]),
+            polygon(&[(2, 2), (4, 2), (4, 4), (2, 4)]),
+        ]);
+        let loops = region.loops().iter().map(|l| l.vertices()).collect::<Vec<&[Point2]>>();
+        assert!(region.is_hole(1));
+        assert_eq!(region.lattice_points(), 48);
+        assert_eq!(region.lattice_points(), CellGrid::rasterize(&loops).filled_area());
+    }
+
+    #[test]
+    fn locate_counts_the_boundary_as_its_own_place() {
+        let square = polygon(&[(0, 0), (4, 0), (4, 4), (0, 4)]);
+        assert_eq!(square.locate(&Point2::new(0, 0)), Location::Boundary);
+        assert_eq!(square.locate(&Point2::new(2, 0)), Location::Boundary);
+        assert_eq!(square.locate(&Point2::new(2, 2)), Location::Inside);
+        assert_eq!(square.locate(&Point2::new(5, 2)), Location::Outside);
+        assert!(square.contains_point(&Point2::new(4, 4)));
+        assert!(square.contains_rect(&Point2::new(0, 0), &Point2::new(4, 4)));
+        assert!(!square.contains_rect(&Point2::new(0, 0), &Point2::new(5, 4)));
+    }
+
+    #[test]
+    fn holes_keep_their_boundary() {
+        let region = OrthoRegion::new(vec![
+            polygon(&[(0, 0), (0, 6), (6, 6), (6, 0)]),
+            polygon(&[(2, 2), (4, 2), (4, 4), (2, 4)]),
+        ]);
+        assert_eq!(region.area(), 32);
+        assert_eq!(region.locate(&Point2::new(3, 3)), Location::Outside);
+        assert_eq!(region.locate(&Point2::new(2, 3)), Location::Boundary);
+        assert_eq!(region.locate(&Point2::new(1, 3)), Location::Inside);
+
+        assert!(!region.contains_rect(&Point2::new(0, 0), &Point2::new(6, 6)));
+        assert!(region.contains_rect(&Point2::new(0, 0), &Point2::new(6, 2)));
+        assert!(region.contains_rect(&Point2::new(2, 2), &Point2::new(4, 2)));
+        assert!(!region.contains_rect(&Point2::new(2, 3), &Point2::new(4, 3)));
+    }
+
+    fn defects(coords: &[(i64, i64)]) -> Vec<Defect> {
+        validate(&coords.iter().map(|&(x, y)| Point2::new(x, y)).collect::<Vec<Point2>>()).defects
+    }
+
+    #[test]
+    fn defects_name_the_edges_and_vertices() {
+        assert_eq!(defects(&[(0, 0), (2, 1), (2, 3), (0, 3)]), vec![Defect::NotAxisAligned { edge: 0 }]);
+
+        // Coming back to the start closes the loop with an empty edge, which also touches the edges next to it
+        assert_eq!(defects(&[(0, 0), (4, 0), (4, 4), (0, 4), (0, 0)]), vec![
+            Defect::DuplicateVertex { first: 0, again: 4 },
+            Defect::SelfIntersection { edge_a: 0, edge_b: 3 },
+        ]);
+
+        let collinear = validate(&[(0, 0), (2, 0), (4, 0), (4, 4), (0, 4)].map(|(x, y)| Point2::new(x, y)));
+        assert_eq!(collinear.defects, vec![Defect::Collinear { vertex: 1 }]);
+        assert!(collinear.is_valid());
+
+        assert_eq!(defects(&[(0, 0), (4, 0), (2, 0), (2, 2), (0, 2)]), vec![
+            Defect::SelfIntersection { edge_a: 0, edge_b: 1 },
+            Defect::SelfIntersection { edge_a: 0, edge_b: 2 },
+        ]);
+
+        // Figure eight, the vertical middle edge runs through the first edge
+        assert_eq!(defects(&[(0, 2), (4, 2), (4, 4), (2, 4), (2, 0), (0, 0)]), vec![
+            Defect::SelfIntersection { edge_a: 0, edge_b: 3 },
+        ]);
+    }
+
+    #[test]
+    fn winding_follows_the_shoelace_sign() {
+        let square = [(0, 0), (4, 0), (4, 4), (0, 4)].map(|(x, y)| Point2::new(x, y));
+        assert_eq!(validate(&square).winding, Some(Winding::CounterClockwise));
+
+        let mut reversed = square;
+        reversed.reverse();
+        assert_eq!(validate(&reversed).winding, Some(Winding::Clockwise));
+
+        let flat = [(0, 0), (4, 0), (4, 0), (0, 0)].map(|(x, y)| Point2::new(x, y));
+        assert_eq!(validate(&flat).winding, None);
+    }
+
+    #[test]
+    fn crossings_between_loops() {
+        let square = |x: i64, y: i64| [(x, y), (x + 2, y), (x + 2, y + 2), (x, y + 2)].map(|(x, y)| Point2::new(x, y));
+
+        // Corner to corner at (2, 2)
+        assert_eq!(crossings(&square(0, 0), &square(2, 2)), vec![(1, 0), (1, 3), (2, 0), (2, 3)]);
+        assert_eq!(crossings(&square(0, 0), &square(3, 3)), vec![]);
+    }
+}
@@ -0,0 +1,158 @@
+use super::geometry::Point2;
+
+// Scanline rasterizer for rectilinear loops on a compressed tile grid. Only the distinct vertex
+// coordinates matter: every one of them becomes a band one tile wide, and the tiles between two
+// of them become a single band as wide as the gap (possibly zero). A cell is a pair of bands, so it
+// stands for a whole block of tiles that are either all filled or all empty.
+// Doubled index 2i is the band at coordinate i, 2i + 1 the gap after it, like in RectilinearPolygon.
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Band {
+    // First real coordinate covered and the number of tiles
+    pub start: i64,
+    pub len: i64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cell {
+    pub x: i64,
+    pub y: i64,
+    pub width: i64,
+    pub height: i64,
+    pub filled: bool,
+}
+
+impl Cell {
+    // Number of real tiles in the cell
+    pub fn weight(&self) -> i64 {
+        self.width * self.height
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct CellGrid {
+    columns: Vec<Band>,
+    rows: Vec<Band>,
+    // filled[v][u] for row band v and column band u
+    filled: Vec<Vec<bool>>,
+}
+
+impl CellGrid {
+    // Fills closed loops (vertices in order, consecutive ones share a row or column) with the even-odd
+    // rule, so loops inside loops are holes. Tiles on a loop are always filled, hole boundaries too.
+    pub fn rasterize(loops: &[&[Point2]]) -> Self {
+        let mut xs = loops.iter().flat_map(|l| l.iter().map(|p| p.x())).collect::<Vec<i64>>();
+        let mut ys = loops.iter().flat_map(|l| l.iter().map(|p| p.y())).collect::<Vec<i64>>();
+        xs.sort_unstable();
+        xs.dedup();
+        ys.sort_unstable();
+        ys.dedup();
+
+        let cells_x = xs.len().saturating_sub(1);
+        let cells_y = ys.len().saturating_sub(1);
+
+        // Scanline over the open gaps between rows: crossing a vertical edge flips between outside and inside
+        let mut flips = vec![vec![false; xs.len()]; cells_y];
+        let edges = loops.iter().flat_map(|l| (0..l.len()).map(|i| (&l[i], &l[(i + 1) % l.len()])));
+        for (a, b) in edges {
+            if a.x() != b.x() {
+                continue;
+            }
+            let x = Self::index(&xs, a.x());
+            let (y1, y2) = (Self::index(&ys, a.y().min(b.y())), Self::index(&ys, a.y().max(b.y())));
+            for row in flips.iter_mut().take(y2).skip(y1) {
+                row[x] = !row[x];
+            }
+        }
+
+        let mut gap_inside = vec![vec![false; cells_x]; cells_y];
+        for (j, row) in gap_inside.iter_mut().enumerate() {
+            let mut inside = false;
+            for (i, cell) in row.iter_mut().enumerate() {
+                inside ^= flips[j][i];
+                *cell = inside;
+            }
+        }
+
+        // Bands on a vertex coordinate are filled if any gap cell touching them is inside
+        let (w, h) = ((2 * xs.len()).saturating_sub(1), (2 * ys.len()).saturating_sub(1));
+        let filled = (0..h)
+            .map(|v| {
+                (0..w)
+                    .map(|u| Self::adjacent_gaps(v, cells_y).any(|j| Self::adjacent_gaps(u, cells_x).any(|i| gap_inside[j][i])))
+                    .collect()
+            })
+            .collect();
+
+        Self { columns: Self::bands(&xs), rows: Self::bands(&ys), filled }
+    }
+
+    // One band per coordinate and one per gap between neighbouring coordinates
+    fn bands(coords: &[i64]) -> Vec<Band> {
+        let mut bands = Vec::with_capacity((2 * coords.len()).saturating_sub(1));
+        for (i, &c) in coords.iter().enumerate() {
+            bands.push(Band { start: c, len: 1 });
+            if let Some(&next) = coords.get(i + 1) {
+                bands.push(Band { start: c + 1, len: next - c - 1 });
+            }
+        }
+        bands
+    }
+
+    // Gap cells next to a doubled index: the gap itself, or the gaps on both sides of a line
+    fn adjacent_gaps(d: usize, cells: usize) -> std::ops::Range<usize> {
+        let lo = if d % 2 == 1 { d / 2 } else { (d / 2).saturating_sub(1) };
+        lo..(d / 2 + 1).min(cells)
+    }
+
+    fn index(coords: &[i64], value: i64) -> usize {
+        coords.binary_search(&value).expect("Coordinate is not a vertex coordinate")
+    }
+
+    pub fn width(&self) -> usize {
+        self.columns.len()
+    }
+
+    pub fn height(&self) -> usize {
+        self.rows.len()
+    }
+
+    pub fn columns(&self) -> &[Band] {
+        &self.columns
+    }
+
+    pub fn rows(&self) -> &[Band] {
+        &self.rows
+    }
+
+    pub fn cell(&self, u: usize, v: usize) -> Cell {
+        let (column, row) = (self.columns[u], self.rows[v]);
+        Cell { x: column.start, y: row.start, width: column.len, height: row.len, filled: self.filled[v][u] }
+    }
+
+    // Row by row, left to right
+    pub fn cells(&self) -> impl Iterator<Item = Cell> + '_ {
+        (0..self.height()).flat_map(move |v| (0..self.width()).map(move |u| self.cell(u, v)))
+    }
+
+    // Number of filled tiles
+    pub fn filled_area(&self) -> i64 {
+        self.cells().filter(|c| c.filled).map(|c| c.weight()).sum()
+    }
+
+    // Band containing a real coordinate, None outside the grid
+    fn band_of(bands: &[Band], value: i64) -> Option<usize> {
+        let idx = bands.partition_point(|b| b.start <= value).checked_sub(1)?;
+        let band = bands[idx];
+        (value < band.start + band.len).then_some(idx)
+    }
+
+    // Cell (u, v) containing the real tile (x, y)
+    pub fn locate(&self, x: i64, y: i64) -> Option<(usize, usize)> {
+        Some((Self::band_of(&self.columns, x)?, Self::band_of(&self.rows, y)?))
+    }
+
+    pub fn contains_tile(&self, x: i64, y: i64) -> bool {
+        self.locate(x, y).is_some_and(|(u, v)| self.filled[v][u])
+    }
+}
@@ -0,0 +1,98 @@
+use super::geometry::Point2;
+use super::raster::CellGrid;
+
+// Rectilinear polygon on a compressed grid. Only the distinct x and y coordinates of the vertices
+// matter, so the plane is cut into the lines at those coordinates and the open intervals between them.
+// Doubled index 2i is the line at xs[i], 2i + 1 the gap between xs[i] and xs[i + 1] (same for y).
+// Every (line|gap, line|gap) combination is a face that is either fully inside the closed polygon or not,
+// which is what CellGrid rasterizes, and a 2D prefix sum over the outside faces answers rectangle
+// containment in O(1). Containment is about tiles: a face without any tile in it (the gap between two
+// neighbouring coordinates, e.g. inside a hole whose loop runs around no tile) can't leave a tile uncovered.
+pub struct RectilinearPolygon {
+    xs: Vec<i64>,
+    ys: Vec<i64>,
+    // prefix[v][u] = number of outside faces with tiles in them and doubled indices < (u, v)
+    prefix: Vec<Vec<u32>>,
+}
+
+impl RectilinearPolygon {
+    // Vertices in order around each loop, consecutive vertices have to share an x or a y coordinate.
+    // Several disjoint loops use the even-odd rule, so loops inside other loops cut out holes.
+    // The boundary of a hole still belongs to the polygon.
+    pub fn from_loops(loops: &[&[Point2]]) -> Self {
+        let grid = CellGrid::rasterize(loops);
+
+        // Every other band sits on a vertex coordinate
+        let xs = grid.columns().iter().step_by(2).map(|b| b.start).collect::<Vec<i64>>();
+        let ys = grid.rows().iter().step_by(2).map(|b| b.start).collect::<Vec<i64>>();
+
+        let (w, h) = (grid.width(), grid.height());
+        let mut prefix = vec![vec![0u32; w + 1]; h + 1];
+        for v in 0..h {
+            for u in 0..w {
+                let cell = grid.cell(u, v);
+                let outside = (!cell.filled && cell.weight() > 0) as u32;
+                prefix[v + 1][u + 1] = outside + prefix[v][u + 1] + prefix[v + 1][u] - prefix[v][u];
+            }
+        }
+
+        Self { xs, ys, prefix }
+    }
+
+    // Compressed (x, y) index of a coordinate that appears among the vertices
+    pub fn compress(&self, p: &Point2) -> Option<(usize, usize)> {
+        Some((self.xs.binary_search(&p.x()).ok()?, self.ys.binary_search(&p.y()).ok()?))
+    }
+
+    // Is the closed axis aligned rectangle spanned by two compressed corners fully inside the closed polygon
+    pub fn contains_compressed_rect(&self, a: (usize, usize), b: (usize, usize)) -> bool {
+        let (u1, u2) = (2 * a.0.min(b.0), 2 * a.0.max(b.0) + 1);
+        let (v1, v2) = (2 * a.1.min(b.1), 2 * a.1.max(b.1) + 1);
+        let p = &self.prefix;
+        p[v2][u2] + p[v1][u1] - p[v1][u2] - p[v2][u1] == 0
+    }
+
+    // Same for corners given as vertex coordinates
+    pub fn contains_rect(&self, a: &Point2, b: &Point2) -> bool {
+        match (self.compress(a), self.compress(b)) {
+            (Some(a), Some(b)) => self.contains_compressed_rect(a, b),
+            _ => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn points(coords: &[(i64, i64)]) -> Vec<Point2> {
+        coords.iter().map(|&(x, y)| Point2::new(x, y)).collect()
+    }
+
+    #[test]
+    fn hole_without_tiles_removes_nothing() {
+        // The hole's loop runs through the tiles (1,1)..(2,2), all four of them on the loop itself
+        let outer = points(&[(0, 0), (0, 4), (4, 4), (4, 0)]);
+        let hole = points(&[(1, 1), (2, 1), (2, 2), (1, 2)]);
+        let loops = [outer.as_slice(), hole.as_slice()];
+
+        assert_eq!(CellGrid::rasterize(&loops).filled_area(), 25);
+        let polygon = RectilinearPolygon::from_loops(&loops);
+        assert!(polygon.contains_rect(&Point2::new(0, 0), &Point2::new(4, 4)));
+        assert!(polygon.contains_rect(&Point2::new(1, 1), &Point2::new(4, 4)));
+    }
+
+    #[test]
+    fn hole_with_tiles_blocks_rectangles_over_it() {
+        let outer = points(&[(0, 0), (0, 6), (6, 6), (6, 0)]);
+        let hole = points(&[(2, 2), (4, 2), (4, 4), (2, 4)]);
+        let loops = [outer.as_slice(), hole.as_slice()];
+
+        // Only the tile (3, 3) is missing
+        assert_eq!(CellGrid::rasterize(&loops).filled_area(), 48);
+        let polygon = RectilinearPolygon::from_loops(&loops);
+        assert!(!polygon.contains_rect(&Point2::new(0, 0), &Point2::new(6, 6)));
+        assert!(polygon.contains_rect(&Point2::new(0, 0), &Point2::new(6, 2)));
+        assert!(polygon.contains_rect(&Point2::new(2, 2), &Point2::new(4, 2)));
+    }
+}
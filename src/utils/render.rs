@@ -0,0 +1,100 @@
+use std::fs;
+use std::io::{self, Write};
+use std::path::PathBuf;
+use std::thread;
+use std::time::Duration;
+
+pub type Rgb = (u8, u8, u8);
+
+// Where the frames of a visualization end up:
+//   "term" or "term:<ms per frame>"  animates in the terminal with ANSI colours
+//   "ppm:<dir>" or "ppm:<dir>:<px per cell>"  writes frame_0000.ppm, frame_0001.ppm, ... into dir
+pub enum FrameSink {
+    Terminal { delay: Duration },
+    Ppm { dir: PathBuf, scale: usize, frame: usize },
+}
+
+impl FrameSink {
+    pub fn parse(target: &str) -> Result<Self, String> {
+        let mut parts = target.splitn(2, ':');
+        let kind = parts.next().unwrap_or("");
+        let rest = parts.next();
+
+        match kind {
+            "term" => {
+                let ms = match rest {
+                    Some(ms) => ms.parse::<u64>().map_err(|_| format!("Invalid frame delay '{}'", ms))?,
+                    None => 100,
+                };
+                Ok(FrameSink::Terminal { delay: Duration::from_millis(ms) })
+            }
+            "ppm" => {
+                let rest = rest.ok_or("ppm target needs a directory, e.g. ppm:frames")?;
+                let (dir, scale) = match rest.rsplit_once(':') {
+                    Some((dir, scale)) if scale.parse::<usize>().is_ok() => (dir, scale.parse().unwrap()),
+                    _ => (rest, 4),
+                };
+                fs::create_dir_all(dir).map_err(|e| format!("Could not create {}: {}", dir, e))?;
+                Ok(FrameSink::Ppm { dir: PathBuf::from(dir), scale: scale.max(1), frame: 0 })
+            }
+            _ => Err(format!("Unknown visualization target '{}', expected term[:ms] or ppm:<dir>[:scale]", target)),
+        }
+    }
+
+    pub fn write(&mut self, frame: &[Vec<Rgb>], caption: &str) -> Result<(), String> {
+        match self {
+            FrameSink::Terminal { delay } => {
+                print_ansi(frame, caption).map_err(|e| format!("Could not draw frame: {}", e))?;
+                thread::sleep(*delay);
+                Ok(())
+            }
+            FrameSink::Ppm { dir, scale, frame: idx } => {
+                let path = dir.join(format!("frame_{:04}.ppm", idx));
+                fs::write(&path, to_ppm(frame, *scale))
+                    .map_err(|e| format!("Could not write {}: {}", path.display(), e))?;
+                *idx += 1;
+                Ok(())
+            }
+        }
+    }
+}
+
+// Two grid rows per terminal line: the upper half block takes the foreground colour
+// and the lower one the background, which keeps cells roughly square
+fn print_ansi(frame: &[Vec<Rgb>], caption: &str) -> io::Result<()> {
+    let mut out = String::from("\x1b[H\x1b[2J");
+
+    for pair in frame.chunks(2) {
+        for (j, &(r, g, b)) in pair[0].iter().enumerate() {
+            let (br, bg, bb) = pair.get(1).map_or((0, 0, 0), |row| row[j]);
+            out.push_str(&format!("\x1b[38;2;{};{};{}m\x1b[48;2;{};{};{}m▀", r, g, b, br, bg, bb));
+        }
+        out.push_str("\x1b[0m\n");
+    }
+    out.push_str(caption);
+    out.push('\n');
+
+    let mut stdout = io::stdout().lock();
+    stdout.write_all(out.as_bytes())?;
+    stdout.flush()
+}
+
+// Binary PPM (P6), every cell becomes a scale x scale block of pixels
+fn to_ppm(frame: &[Vec<Rgb>], scale: usize) -> Vec<u8> {
+    let height = frame.len() * scale;
+    let width = frame.first().map_or(0, |row| row.len()) * scale;
+
+    let mut data = format!("P6\n{} {}\n255\n", width, height).into_bytes();
+    for row in frame {
+        let mut line = Vec::with_capacity(width * 3);
+        for &(r, g, b) in row {
+            for _ in 0..scale {
+                line.extend_from_slice(&[r, g, b]);
+            }
+        }
+        for _ in 0..scale {
+            data.extend_from_slice(&line);
+        }
+    }
+    data
+}
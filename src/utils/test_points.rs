@@ -0,0 +1,17 @@
+use super::geometry::Point;
+
+// xorshift, good enough to scatter some test data
+pub fn xorshift(seed: u64) -> impl FnMut() -> u64 {
+    let mut state = seed.wrapping_mul(0x9E3779B97F4A7C15) | 1;
+    move || {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        state
+    }
+}
+
+pub fn random_points<const N: usize>(seed: u64, n: usize, range: i64) -> Vec<Point<N>> {
+    let mut next = xorshift(seed);
+    (0..n).map(|_| Point { coords: std::array::from_fn(|_| (next() % range as u64) as i64) }).collect()
+}
@@ -0,0 +1,163 @@
+// Disjoint set forest with union by size.
+// With rollback enabled, find skips path compression so every union can be undone again,
+// which is what offline algorithms (divide and conquer over time etc.) need.
+pub struct UnionFind {
+    parent: Vec<usize>,
+    size: Vec<usize>,
+    components: usize,
+    // (attached root, root it was attached to) for every union since creation
+    history: Option<Vec<(usize, usize)>>,
+}
+
+impl UnionFind {
+    pub fn new(size: usize) -> Self {
+        Self {
+            parent: (0..size).collect(),
+            size: vec![1; size],
+            components: size,
+            history: None,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.parent.len()
+    }
+
+    pub fn find(&mut self, x: usize) -> usize {
+        let mut root = x;
+        while self.parent[root] != root {
+            root = self.parent[root];
+        }
+
+        // Path compression, second pass so long chains can't blow the stack
+        if self.history.is_none() {
+            let mut current = x;
+            while self.parent[current] != root {
+                let next = self.parent[current];
+                self.parent[current] = root;
+                current = next;
+            }
+        }
+
+        root
+    }
+
+    // Returns false if x and y were already in the same set
+    pub fn union(&mut self, x: usize, y: usize) -> bool {
+        let mut root_x = self.find(x);
+        let mut root_y = self.find(y);
+
+        if root_x == root_y {
+            return false;
+        }
+
+        // Always hang the smaller tree below the bigger one
+        if self.size[root_x] < self.size[root_y] {
+            std::mem::swap(&mut root_x, &mut root_y);
+        }
+        self.parent[root_y] = root_x;
+        self.size[root_x] += self.size[root_y];
+        self.components -= 1;
+
+        if let Some(history) = &mut self.history {
+            history.push((root_y, root_x));
+        }
+        true
+    }
+
+    // Size of the set x belongs to
+    pub fn size(&mut self, x: usize) -> usize {
+        let root = self.find(x);
+        self.size[root]
+    }
+
+    pub fn components(&self) -> usize {
+        self.components
+    }
+
+    // Sizes of all sets, largest first
+    pub fn component_sizes(&self) -> Vec<usize> {
+        let mut sizes = (0..self.len())
+            .filter(|&i| self.parent[i] == i)
+            .map(|i| self.size[i])
+            .collect::<Vec<usize>>();
+        sizes.sort_unstable_by(|a, b| b.cmp(a));
+        sizes
+    }
+}
+
+// Nothing in the days undoes unions or asks these directly yet, the tests below keep them honest
+#[allow(dead_code)]
+impl UnionFind {
+    pub fn with_rollback(size: usize) -> Self {
+        Self {
+            history: Some(vec![]),
+            ..Self::new(size)
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.parent.is_empty()
+    }
+
+    pub fn connected(&mut self, x: usize, y: usize) -> bool {
+        self.find(x) == self.find(y)
+    }
+
+    pub fn is_connected(&self) -> bool {
+        self.components <= 1
+    }
+
+    // Marker to roll back to later, only available when created with_rollback
+    pub fn snapshot(&self) -> usize {
+        self.history.as_ref().expect("UnionFind was created without rollback").len()
+    }
+
+    // Undoes all unions made after the snapshot was taken
+    pub fn rollback(&mut self, snapshot: usize) {
+        let history = self.history.as_mut().expect("UnionFind was created without rollback");
+        while history.len() > snapshot {
+            let (child, root) = history.pop().unwrap();
+            self.parent[child] = child;
+            self.size[root] -= self.size[child];
+            self.components += 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rollback_restores_earlier_sets() {
+        let mut uf = UnionFind::with_rollback(6);
+        uf.union(0, 1);
+        uf.union(2, 3);
+        let snapshot = uf.snapshot();
+        let (components, size) = (uf.components(), uf.size(0));
+
+        uf.union(1, 2);
+        uf.union(4, 5);
+        uf.union(0, 5);
+        assert!(uf.is_connected());
+        assert_eq!(uf.size(3), 6);
+
+        uf.rollback(snapshot);
+        assert_eq!((uf.components(), uf.size(0)), (components, size));
+        assert_eq!((uf.size(2), uf.size(4)), (2, 1));
+        assert!(uf.connected(2, 3) && !uf.connected(1, 2));
+    }
+
+    #[test]
+    fn find_walks_long_chains_without_recursion() {
+        // Union by size never builds a chain like this, so hang every element below its predecessor
+        let n = 1_000_000;
+        let mut uf = UnionFind::new(n);
+        uf.parent = (0..n).map(|i| i.saturating_sub(1)).collect();
+
+        assert_eq!(uf.find(n - 1), 0);
+        // Path compression hung the whole chain directly below the root
+        assert!(uf.parent.iter().all(|&p| p == 0));
+    }
+}